@@ -1,4 +1,16 @@
-const COMMANDS: &[&str] = &["check", "download_and_install"];
+const COMMANDS: &[&str] = &[
+    "check",
+    "download_and_install",
+    "install_and_relaunch",
+    "relaunch",
+    "mark_update_successful",
+    "get_update_result",
+    "cancel",
+    "pause",
+    "resume",
+    "set_rate_limit",
+    "install_from_file",
+];
 
 fn main() {
     tauri_plugin::Builder::new(COMMANDS)
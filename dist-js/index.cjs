@@ -46,6 +46,39 @@ class Update extends core.Resource {
             rid: this.rid,
         });
     }
+    /** Downloads the updater package, installs it and restarts the app */
+    async installAndRelaunch(onEvent) {
+        const channel = new core.Channel();
+        if (onEvent) {
+            channel.onmessage = onEvent;
+        }
+        await core.invoke("plugin:universal-updater|install_and_relaunch", {
+            onEvent: channel,
+            rid: this.rid,
+        });
+    }
+    /** Cancels a running download of this update */
+    async cancel() {
+        await core.invoke("plugin:universal-updater|cancel", { rid: this.rid });
+    }
+    /** Pauses a running download of this update */
+    async pause() {
+        await core.invoke("plugin:universal-updater|pause", { rid: this.rid });
+    }
+    /** Resumes a paused download of this update */
+    async resume() {
+        await core.invoke("plugin:universal-updater|resume", { rid: this.rid });
+    }
+    /**
+     * Limits the download rate of this update, `null` removes the limit.
+     * Takes effect immediately on a running download.
+     */
+    async setRateLimit(bytesPerSecond) {
+        await core.invoke("plugin:universal-updater|set_rate_limit", {
+            rid: this.rid,
+            bytesPerSecond,
+        });
+    }
     async close() {
         await this.downloadedBytes?.close();
         await super.close();
@@ -60,6 +93,47 @@ async function check(options) {
         ...options,
     }).then((meta) => (meta.available ? new Update(meta) : null));
 }
+/**
+ * Installs the update package at `path` as `version`, e.g. from removable media on an offline machine.
+ *
+ * The signature is read from the `.sig` file next to the package and always verified, so the plugin must be
+ * configured with a `pubkey`. Requires the `allow-install-from-file` permission, which is not in the default set.
+ */
+async function installFromFile(path, version, onEvent) {
+    const channel = new core.Channel();
+    if (onEvent) {
+        channel.onmessage = onEvent;
+    }
+    await core.invoke("plugin:universal-updater|install_from_file", {
+        path,
+        version,
+        onEvent: channel,
+    });
+}
+/**
+ * Restarts the app with its original arguments.
+ *
+ * Requires the `allow-relaunch` permission, which is not in the default set.
+ */
+async function relaunch() {
+    await core.invoke("plugin:universal-updater|relaunch");
+}
+/**
+ * Confirms the running version started correctly after an update.
+ *
+ * When `rollbackWindow` is configured, the previous version is restored if this is not called in time.
+ */
+async function markUpdateSuccessful() {
+    await core.invoke("plugin:universal-updater|mark_update_successful");
+}
+/** Returns the outcome of the last update, e.g. to show what's new after updating */
+async function getUpdateResult() {
+    return await core.invoke("plugin:universal-updater|get_update_result");
+}
 
 exports.Update = Update;
 exports.check = check;
+exports.getUpdateResult = getUpdateResult;
+exports.installFromFile = installFromFile;
+exports.markUpdateSuccessful = markUpdateSuccessful;
+exports.relaunch = relaunch;
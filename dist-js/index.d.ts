@@ -17,6 +17,11 @@ interface CheckOptions {
      * Target identifier for the running application. This is sent to the backend.
      */
     target?: string;
+    /**
+     * Whether the update is downloaded in the background, e.g. by a scheduled check.
+     * Background downloads are limited to the configured `backgroundDownloadLimit`.
+     */
+    background?: boolean;
 }
 interface UpdateMetadata {
     rid: number;
@@ -26,6 +31,31 @@ interface UpdateMetadata {
     date?: string;
     body?: string;
 }
+/** Outcome of the last update, reported on the first launch after it */
+interface UpdateResult {
+    /** Set when this is the first launch of a version installed by the updater */
+    justUpdated?: {
+        from: string;
+        to: string;
+        notes?: string;
+    };
+    /** Set when the last install attempt failed */
+    failed?: {
+        version: string;
+        error: string;
+    };
+}
+/** Error rejected by the updater commands */
+interface UpdaterError {
+    /** Stable identifier of the error kind, e.g. `checkStatus` or `cancelled` */
+    code: string;
+    message: string;
+    /**
+     * Additional data for some errors, e.g. the `url`, `status` and truncated `body`
+     * of a failed release JSON request
+     */
+    details: Record<string, unknown> | null;
+}
 /** Updater download event */
 type DownloadEvent = {
     event: "Started";
@@ -36,9 +66,37 @@ type DownloadEvent = {
     event: "Progress";
     data: {
         chunkLength: number;
+        /** Bytes downloaded so far */
+        downloaded: number;
+        /** Total size of the download, if known */
+        total?: number;
+        /** Smoothed download rate */
+        bytesPerSecond: number;
+        /** Estimated seconds until the download finishes, if the total size is known */
+        etaSeconds?: number;
     };
 } | {
     event: "Finished";
+} | {
+    event: "Mirror";
+    data: {
+        url: string;
+    };
+} | {
+    event: "Paused";
+} | {
+    event: "Resumed";
+} | {
+    event: "Verifying";
+} | {
+    event: "Extracting";
+} | {
+    event: "Installing";
+} | {
+    event: "Failed";
+    data: {
+        error: string;
+    };
 };
 declare class Update extends Resource {
     available: boolean;
@@ -54,9 +112,43 @@ declare class Update extends Resource {
     install(): Promise<void>;
     /** Downloads the updater package and installs it */
     downloadAndInstall(onEvent?: (progress: DownloadEvent) => void): Promise<void>;
+    /** Downloads the updater package, installs it and restarts the app */
+    installAndRelaunch(onEvent?: (progress: DownloadEvent) => void): Promise<void>;
+    /** Cancels a running download of this update */
+    cancel(): Promise<void>;
+    /** Pauses a running download of this update */
+    pause(): Promise<void>;
+    /** Resumes a paused download of this update */
+    resume(): Promise<void>;
+    /**
+     * Limits the download rate of this update, `null` removes the limit.
+     * Takes effect immediately on a running download.
+     */
+    setRateLimit(bytesPerSecond: number | null): Promise<void>;
     close(): Promise<void>;
 }
 /** Check for updates, resolves to `null` if no updates are available */
 declare function check(options?: CheckOptions): Promise<Update | null>;
-export type { CheckOptions, DownloadEvent };
-export { check, Update };
+/**
+ * Installs the update package at `path` as `version`, e.g. from removable media on an offline machine.
+ *
+ * The signature is read from the `.sig` file next to the package and always verified, so the plugin must be
+ * configured with a `pubkey`. Requires the `allow-install-from-file` permission, which is not in the default set.
+ */
+declare function installFromFile(path: string, version: string, onEvent?: (progress: DownloadEvent) => void): Promise<void>;
+/**
+ * Restarts the app with its original arguments.
+ *
+ * Requires the `allow-relaunch` permission, which is not in the default set.
+ */
+declare function relaunch(): Promise<void>;
+/**
+ * Confirms the running version started correctly after an update.
+ *
+ * When `rollbackWindow` is configured, the previous version is restored if this is not called in time.
+ */
+declare function markUpdateSuccessful(): Promise<void>;
+/** Returns the outcome of the last update, e.g. to show what's new after updating */
+declare function getUpdateResult(): Promise<UpdateResult>;
+export type { CheckOptions, DownloadEvent, UpdateResult, UpdaterError };
+export { check, getUpdateResult, installFromFile, markUpdateSuccessful, relaunch, Update, };
@@ -18,6 +18,11 @@ interface CheckOptions {
    * Target identifier for the running application. This is sent to the backend.
    */
   target?: string;
+  /**
+   * Whether the update is downloaded in the background, e.g. by a scheduled check.
+   * Background downloads are limited to the configured `backgroundDownloadLimit`.
+   */
+  background?: boolean;
 }
 
 interface UpdateMetadata {
@@ -29,11 +34,52 @@ interface UpdateMetadata {
   body?: string;
 }
 
+/** Outcome of the last update, reported on the first launch after it */
+interface UpdateResult {
+  /** Set when this is the first launch of a version installed by the updater */
+  justUpdated?: { from: string; to: string; notes?: string };
+  /** Set when the last install attempt failed */
+  failed?: { version: string; error: string };
+}
+
+/** Error rejected by the updater commands */
+interface UpdaterError {
+  /** Stable identifier of the error kind, e.g. `checkStatus` or `cancelled` */
+  code: string;
+  message: string;
+  /**
+   * Additional data for some errors, e.g. the `url`, `status` and truncated `body`
+   * of a failed release JSON request
+   */
+  details: Record<string, unknown> | null;
+}
+
 /** Updater download event */
 type DownloadEvent =
   | { event: "Started"; data: { contentLength?: number } }
-  | { event: "Progress"; data: { chunkLength: number } }
-  | { event: "Finished" };
+  | {
+      event: "Progress";
+      data: {
+        chunkLength: number;
+        /** Bytes downloaded so far */
+        downloaded: number;
+        /** Total size of the download, if known */
+        total?: number;
+        /** Smoothed download rate */
+        bytesPerSecond: number;
+        /** Estimated seconds until the download finishes, if the total size is known */
+        etaSeconds?: number;
+      };
+    }
+  | { event: "Finished" }
+  /** The download is served by `url`, sent again when switching to another mirror */
+  | { event: "Mirror"; data: { url: string } }
+  | { event: "Paused" }
+  | { event: "Resumed" }
+  | { event: "Verifying" }
+  | { event: "Extracting" }
+  | { event: "Installing" }
+  | { event: "Failed"; data: { error: string } };
 
 class Update extends Resource {
   available: boolean;
@@ -94,6 +140,46 @@ class Update extends Resource {
     });
   }
 
+  /** Downloads the updater package, installs it and restarts the app */
+  async installAndRelaunch(
+    onEvent?: (progress: DownloadEvent) => void
+  ): Promise<void> {
+    const channel = new Channel<DownloadEvent>();
+    if (onEvent) {
+      channel.onmessage = onEvent;
+    }
+    await invoke("plugin:universal-updater|install_and_relaunch", {
+      onEvent: channel,
+      rid: this.rid,
+    });
+  }
+
+  /** Cancels a running download of this update */
+  async cancel(): Promise<void> {
+    await invoke("plugin:universal-updater|cancel", { rid: this.rid });
+  }
+
+  /** Pauses a running download of this update */
+  async pause(): Promise<void> {
+    await invoke("plugin:universal-updater|pause", { rid: this.rid });
+  }
+
+  /** Resumes a paused download of this update */
+  async resume(): Promise<void> {
+    await invoke("plugin:universal-updater|resume", { rid: this.rid });
+  }
+
+  /**
+   * Limits the download rate of this update, `null` removes the limit.
+   * Takes effect immediately on a running download.
+   */
+  async setRateLimit(bytesPerSecond: number | null): Promise<void> {
+    await invoke("plugin:universal-updater|set_rate_limit", {
+      rid: this.rid,
+      bytesPerSecond,
+    });
+  }
+
   async close(): Promise<void> {
     await this.downloadedBytes?.close();
     await super.close();
@@ -111,5 +197,57 @@ async function check(options?: CheckOptions): Promise<Update | null> {
   }).then((meta) => (meta.available ? new Update(meta) : null));
 }
 
-export type { CheckOptions, DownloadEvent };
-export { check, Update };
+/**
+ * Installs the update package at `path` as `version`, e.g. from removable media on an offline machine.
+ *
+ * The signature is read from the `.sig` file next to the package and always verified, so the plugin must be
+ * configured with a `pubkey`. Requires the `allow-install-from-file` permission, which is not in the default set.
+ */
+async function installFromFile(
+  path: string,
+  version: string,
+  onEvent?: (progress: DownloadEvent) => void
+): Promise<void> {
+  const channel = new Channel<DownloadEvent>();
+  if (onEvent) {
+    channel.onmessage = onEvent;
+  }
+  await invoke("plugin:universal-updater|install_from_file", {
+    path,
+    version,
+    onEvent: channel,
+  });
+}
+
+/**
+ * Restarts the app with its original arguments.
+ *
+ * Requires the `allow-relaunch` permission, which is not in the default set.
+ */
+async function relaunch(): Promise<void> {
+  await invoke("plugin:universal-updater|relaunch");
+}
+
+/**
+ * Confirms the running version started correctly after an update.
+ *
+ * When `rollbackWindow` is configured, the previous version is restored if this is not called in time.
+ */
+async function markUpdateSuccessful(): Promise<void> {
+  await invoke("plugin:universal-updater|mark_update_successful");
+}
+
+/** Returns the outcome of the last update, e.g. to show what's new after updating */
+async function getUpdateResult(): Promise<UpdateResult> {
+  return await invoke<UpdateResult>("plugin:universal-updater|get_update_result");
+}
+
+export type { CheckOptions, DownloadEvent, UpdateResult, UpdaterError };
+export {
+  check,
+  getUpdateResult,
+  installFromFile,
+  markUpdateSuccessful,
+  relaunch,
+  Update,
+};
@@ -0,0 +1,74 @@
+//! Release data from the artifacts of the Tauri bundler, used by the command line tools.
+
+use std::{collections::HashMap, fs, path::Path};
+
+use sha2::{Digest, Sha256};
+use url::Url;
+
+use crate::{naming, ReleaseManifestPlatform, Result};
+
+/// Reads the updater packages in `dir` into the platforms of a release, with their SHA-256 and the signature of
+/// their `.sig` file if there is one.
+///
+/// Packages whose name does not tell their architecture are used for the `targets` of their OS, and fail if
+/// there is none. Package URLs are the file names joined to `base_url`, which should end with a `/`.
+pub fn platforms_from_dir(
+    dir: &Path,
+    base_url: &Url,
+    targets: &[String],
+) -> Result<HashMap<String, ReleaseManifestPlatform>> {
+    let mut files = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    files.retain(|path| path.is_file());
+    files.sort();
+
+    for path in &files {
+        let name = file_name(path);
+        let (Some(os), Some(named)) = (naming::package_os(name), naming::package_targets(name))
+        else {
+            continue;
+        };
+        let has_target = targets
+            .iter()
+            .any(|target| target.split_once('-').map(|(os, _)| os) == Some(os));
+        if named.is_empty() && !has_target {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "`{name}` does not name its architecture, pass a `{os}-<arch>` target for it"
+                ),
+            )
+            .into());
+        }
+    }
+
+    let mut platforms = HashMap::new();
+    for (target, path) in
+        naming::select_packages_with_targets(&files, |path| file_name(path), targets)
+    {
+        let name = file_name(path);
+        let signature_path = path.with_file_name(format!("{name}.sig"));
+        let signature = if signature_path.exists() {
+            Some(fs::read_to_string(signature_path)?.trim().to_string())
+        } else {
+            None
+        };
+
+        let platform = ReleaseManifestPlatform {
+            url: base_url.join(name)?,
+            urls: Vec::new(),
+            signature,
+            sha256: Some(hex::encode(Sha256::digest(fs::read(path)?))),
+            patches: Vec::new(),
+        };
+        platforms.insert(target, platform);
+    }
+    Ok(platforms)
+}
+
+fn file_name(path: &Path) -> &str {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default()
+}
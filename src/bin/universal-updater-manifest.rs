@@ -0,0 +1,129 @@
+//! Generates the release JSON of an update from the artifacts of the Tauri bundler.
+//!
+//! ```text
+//! universal-updater-manifest --version 1.2.0 --notes NOTES.md \
+//!     --base-url https://releases.example.com/1.2.0/ target/release/bundle/updater
+//! ```
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use clap::Parser;
+use semver::Version;
+use tauri_plugin_universal_updater::{artifacts, naming, RemoteRelease};
+use time::OffsetDateTime;
+use url::Url;
+
+#[derive(Parser)]
+#[command(about = "Generates the release JSON of an update from bundler artifacts")]
+struct Args {
+    /// Version of the release.
+    #[arg(long)]
+    version: Version,
+    /// File with the release notes.
+    #[arg(long)]
+    notes: Option<PathBuf>,
+    /// URL the artifacts are uploaded to, the package URLs are resolved against it.
+    #[arg(long)]
+    base_url: Url,
+    /// Release date as RFC 3339, defaults to now.
+    #[arg(long)]
+    pub_date: Option<String>,
+    /// Date as RFC 3339 after which clients reject the release JSON as stale, so it must be republished before.
+    #[arg(long)]
+    expires: Option<String>,
+    /// `json_target` of a package whose name does not tell its architecture, e.g. `darwin-aarch64` for
+    /// `MyApp.app.tar.gz`. Repeat it for packages of several OSes.
+    #[arg(long = "target", value_parser = naming::parse_target)]
+    targets: Vec<String>,
+    /// File to write the release JSON to, defaults to stdout.
+    #[arg(long, short)]
+    output: Option<PathBuf>,
+    /// Directory with the updater packages and their `.sig` files.
+    artifacts: PathBuf,
+}
+
+fn main() -> ExitCode {
+    match run(Args::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let mut base_url = args.base_url;
+    if !base_url.path().ends_with('/') {
+        base_url.set_path(&format!("{}/", base_url.path()));
+    }
+
+    let notes = args.notes.map(|path| read_to_string(&path)).transpose()?;
+    let pub_date = match args.pub_date {
+        Some(date) => OffsetDateTime::parse(&date, &time::format_description::well_known::Rfc3339)
+            .map_err(|e| format!("invalid `--pub-date`: {e}"))?,
+        None => OffsetDateTime::now_utc().replace_nanosecond(0).unwrap(),
+    };
+    let expires = args
+        .expires
+        .map(|date| {
+            OffsetDateTime::parse(&date, &time::format_description::well_known::Rfc3339)
+                .map_err(|e| format!("invalid `--expires`: {e}"))
+        })
+        .transpose()?;
+
+    let platforms = artifacts::platforms_from_dir(&args.artifacts, &base_url, &args.targets)
+        .map_err(|e| format!("failed to read `{}`: {e}", args.artifacts.display()))?;
+    if platforms.is_empty() {
+        return Err(format!(
+            "no updater packages found in `{}`",
+            args.artifacts.display()
+        ));
+    }
+    for (target, platform) in &platforms {
+        eprintln!("{target}: {}", platform.url);
+        if platform.signature.is_none() {
+            eprintln!("warning: `{}` has no `.sig` file", platform.url);
+        }
+    }
+
+    let release = RemoteRelease {
+        version: args.version,
+        notes,
+        pub_date: Some(pub_date),
+        expires,
+        platforms,
+    };
+    let manifest = to_manifest(&release)?;
+
+    // the manifest is only written if the plugin reads back the same release
+    let parsed = RemoteRelease::from_manifest(manifest.as_bytes(), &base_url)
+        .map_err(|e| format!("the generated manifest does not parse: {e}"))?;
+    if to_manifest(&parsed)? != manifest {
+        return Err("the generated manifest does not round-trip through the plugin".into());
+    }
+
+    match args.output {
+        Some(output) => fs::write(&output, manifest + "\n")
+            .map_err(|e| format!("failed to write `{}`: {e}", output.display())),
+        None => {
+            println!("{manifest}");
+            Ok(())
+        }
+    }
+}
+
+fn to_manifest(release: &RemoteRelease) -> Result<String, String> {
+    // through a `Value` so the platforms are sorted by key
+    serde_json::to_value(release)
+        .and_then(|manifest| serde_json::to_string_pretty(&manifest))
+        .map_err(|e| format!("failed to serialize the manifest: {e}"))
+}
+
+fn read_to_string(path: &Path) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| format!("failed to read `{}`: {e}", path.display()))
+}
@@ -0,0 +1,517 @@
+//! Serves a directory of releases on localhost to test updates end to end.
+//!
+//! The directory holds a folder of bundler artifacts and `.sig` files for every version, with optional release
+//! notes in `notes.md`. The newest version is offered as the update:
+//!
+//! ```text
+//! releases/
+//!   1.1.0/app_1.1.0_amd64.AppImage.tar.gz
+//!   1.2.0/app_1.2.0_amd64.AppImage.tar.gz
+//!   1.2.0/app_1.2.0_amd64.AppImage.tar.gz.sig
+//!   1.2.0/notes.md
+//! ```
+//!
+//! ```text
+//! universal-updater-serve releases --rate 200000 --drop-rate 0.3 --error-rate 0.1
+//! ```
+
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    io,
+    net::{IpAddr, SocketAddr},
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use bytes::Bytes;
+use clap::Parser;
+use futures_util::stream;
+use http::{
+    header::{ACCEPT_RANGES, CONTENT_LENGTH, CONTENT_RANGE, CONTENT_TYPE, RANGE},
+    Method, Request, Response, StatusCode,
+};
+use http_body_util::{combinators::UnsyncBoxBody, BodyExt, Empty, Full, StreamBody};
+use hyper::{body::Frame, server::conn::http1, service::service_fn};
+use hyper_util::rt::TokioIo;
+use semver::Version;
+use tauri_plugin_universal_updater::{artifacts, naming, RemoteRelease};
+use time::OffsetDateTime;
+use tokio::{
+    io::{AsyncReadExt, AsyncSeekExt},
+    net::TcpListener,
+};
+use url::Url;
+
+/// Size of the chunks packages are sent in, and the granularity of the rate limit.
+const CHUNK_SIZE: usize = 16 * 1024;
+
+type Body = UnsyncBoxBody<Bytes, io::Error>;
+
+#[derive(Parser)]
+#[command(about = "Serves a directory of releases to test updates")]
+struct Args {
+    /// Directory with a folder of artifacts for every version.
+    dir: PathBuf,
+    #[arg(long, default_value = "127.0.0.1")]
+    host: IpAddr,
+    #[arg(long, short, default_value_t = 8080)]
+    port: u16,
+    /// Path of the release JSON listing every target.
+    #[arg(long, default_value = "/latest.json", value_parser = parse_path)]
+    manifest: String,
+    /// Route answering with the release of a single target, or with a 204 if `{{current_version}}` is up to date.
+    ///
+    /// `{{target}}` matches `json_target` keys like `linux-x86_64`, or only the OS if the route has an `{{arch}}`.
+    #[arg(
+        long,
+        default_value = "/update/{{target}}/{{current_version}}",
+        value_parser = parse_path
+    )]
+    route: String,
+    /// `json_target` of a package whose name does not tell its architecture, e.g. `darwin-aarch64` for
+    /// `MyApp.app.tar.gz`. Repeat it for packages of several OSes.
+    #[arg(long = "target", value_parser = naming::parse_target)]
+    targets: Vec<String>,
+    /// URL the server is reached at, used for the package URLs. Defaults to the listen address.
+    #[arg(long)]
+    public_url: Option<Url>,
+    /// Limits package downloads to this many bytes per second.
+    #[arg(long)]
+    rate: Option<u64>,
+    /// Chance of dropping the connection partway through a package download, between 0 and 1.
+    #[arg(long, default_value_t = 0.0)]
+    drop_rate: f64,
+    /// Chance of answering any request with a 503, between 0 and 1.
+    #[arg(long, default_value_t = 0.0)]
+    error_rate: f64,
+}
+
+struct Server {
+    args: Args,
+    base_url: Url,
+    /// The release of the newest version folder, rebuilt when a file in it changes.
+    cache: Mutex<Option<CachedRelease>>,
+}
+
+struct CachedRelease {
+    dir: PathBuf,
+    files: Vec<FileStamp>,
+    release: RemoteRelease,
+}
+
+/// Name, modification time and size of a file, telling whether it changed.
+type FileStamp = (std::ffi::OsString, SystemTime, u64);
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args = Args::parse();
+    let address = SocketAddr::new(args.host, args.port);
+    let listener = match TcpListener::bind(address).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("error: failed to listen on {address}: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut base_url = args
+        .public_url
+        .clone()
+        .unwrap_or_else(|| Url::parse(&format!("http://{address}/")).unwrap());
+    if !base_url.path().ends_with('/') {
+        base_url.set_path(&format!("{}/", base_url.path()));
+    }
+    let (manifest_url, route_url) = match (
+        base_url.join(args.manifest.trim_start_matches('/')),
+        base_url.join(args.route.trim_start_matches('/')),
+    ) {
+        (Ok(manifest_url), Ok(route_url)) => (manifest_url, route_url),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("error: invalid route for `{base_url}`: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    eprintln!("serving `{}` on http://{address}", args.dir.display());
+    eprintln!("  release JSON: {manifest_url}");
+    eprintln!("  dynamic:      {route_url}");
+
+    let server = Arc::new(Server {
+        args,
+        base_url,
+        cache: Mutex::new(None),
+    });
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(e) => {
+                eprintln!("error: failed to accept a connection: {e}");
+                continue;
+            }
+        };
+        let server = server.clone();
+        tokio::spawn(async move {
+            let service = service_fn(|request| {
+                let server = server.clone();
+                async move { Ok::<_, Infallible>(server.handle(request).await) }
+            });
+            // dropped connections are expected when simulating failures
+            let _ = http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await;
+        });
+    }
+}
+
+impl Server {
+    async fn handle<B>(&self, request: Request<B>) -> Response<Body> {
+        let response = if fastrand::f64() < self.args.error_rate {
+            text(StatusCode::SERVICE_UNAVAILABLE, "simulated failure")
+        } else {
+            match self.route(&request).await {
+                Ok(response) => response,
+                Err(e) => text(StatusCode::INTERNAL_SERVER_ERROR, &e),
+            }
+        };
+        eprintln!(
+            "{} {} -> {}",
+            request.method(),
+            request.uri(),
+            response.status()
+        );
+        response
+    }
+
+    async fn route<B>(&self, request: &Request<B>) -> Result<Response<Body>, String> {
+        if request.method() != Method::GET && request.method() != Method::HEAD {
+            return Ok(text(StatusCode::METHOD_NOT_ALLOWED, "method not allowed"));
+        }
+
+        let path = request.uri().path();
+        if path == self.args.manifest {
+            return match self.latest_release()? {
+                Some(release) => json(&release),
+                None => Ok(text(StatusCode::NOT_FOUND, "no release found")),
+            };
+        }
+        if let Some(params) = match_route(&self.args.route, path) {
+            return self.dynamic_release(&params);
+        }
+
+        let segments = path
+            .trim_start_matches('/')
+            .split('/')
+            .map(|segment| percent_encoding::percent_decode_str(segment).decode_utf8_lossy())
+            .collect::<Vec<_>>();
+        match &segments[..] {
+            [version, file]
+                if Version::parse(version.trim_start_matches('v')).is_ok()
+                    && !file.contains(['/', '\\'])
+                    && !file.starts_with('.') =>
+            {
+                let path = self.args.dir.join(version.as_ref()).join(file.as_ref());
+                self.package(request, &path).await
+            }
+            _ => Ok(text(StatusCode::NOT_FOUND, "not found")),
+        }
+    }
+
+    /// The release of the newest version folder, reusing the last one while none of its files changed.
+    fn latest_release(&self) -> Result<Option<RemoteRelease>, String> {
+        let read_error =
+            |e: io::Error| format!("failed to read `{}`: {e}", self.args.dir.display());
+        let mut newest: Option<(Version, PathBuf)> = None;
+        for entry in std::fs::read_dir(&self.args.dir).map_err(read_error)? {
+            let path = entry.map_err(read_error)?.path();
+            let Some(version) = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| Version::parse(name.trim_start_matches('v')).ok())
+            else {
+                continue;
+            };
+            if path.is_dir()
+                && newest
+                    .as_ref()
+                    .map_or(true, |(newest, _)| &version > newest)
+            {
+                newest = Some((version, path));
+            }
+        }
+        let Some((version, dir)) = newest else {
+            return Ok(None);
+        };
+
+        let files =
+            file_stamps(&dir).map_err(|e| format!("failed to read `{}`: {e}", dir.display()))?;
+        let mut cache = self.cache.lock().unwrap();
+        if let Some(cached) = cache
+            .as_ref()
+            .filter(|cached| cached.dir == dir && cached.files == files)
+        {
+            return Ok(Some(cached.release.clone()));
+        }
+
+        let folder = dir.file_name().unwrap().to_string_lossy();
+        let base_url = self
+            .base_url
+            .join(&format!("{folder}/"))
+            .map_err(|e| e.to_string())?;
+        let platforms = artifacts::platforms_from_dir(&dir, &base_url, &self.args.targets)
+            .map_err(|e| format!("failed to read `{}`: {e}", dir.display()))?;
+        let notes = std::fs::read_to_string(dir.join("notes.md")).ok();
+        let pub_date = std::fs::metadata(&dir)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .map(|modified| {
+                OffsetDateTime::from(modified)
+                    .replace_nanosecond(0)
+                    .unwrap()
+            });
+
+        let release = RemoteRelease {
+            version,
+            notes,
+            pub_date,
+            expires: None,
+            platforms,
+        };
+        *cache = Some(CachedRelease {
+            dir,
+            files,
+            release: release.clone(),
+        });
+        Ok(Some(release))
+    }
+
+    /// The release narrowed to the target of the route, as an update server picking the package would answer.
+    fn dynamic_release(&self, params: &HashMap<String, String>) -> Result<Response<Body>, String> {
+        let Some(mut release) = self.latest_release()? else {
+            return Ok(empty(StatusCode::NO_CONTENT));
+        };
+
+        if let Some(current_version) = params.get("current_version") {
+            let current_version = Version::parse(current_version.trim_start_matches('v'))
+                .map_err(|e| format!("invalid current version `{current_version}`: {e}"))?;
+            if current_version >= release.version {
+                return Ok(empty(StatusCode::NO_CONTENT));
+            }
+        }
+
+        let target = match (params.get("target"), params.get("arch")) {
+            (Some(target), Some(arch)) => format!("{target}-{arch}"),
+            (Some(target), None) => target.clone(),
+            (None, _) => return json(&release),
+        };
+        match release.platforms.remove(&target) {
+            Some(platform) => {
+                release.platforms = HashMap::from([(target, platform)]);
+                json(&release)
+            }
+            None => Ok(text(StatusCode::NOT_FOUND, "no package for the target")),
+        }
+    }
+
+    /// Streams a package, honoring `Range` requests and simulating the configured slow link and drops.
+    async fn package<B>(
+        &self,
+        request: &Request<B>,
+        path: &Path,
+    ) -> Result<Response<Body>, String> {
+        let Ok(mut file) = tokio::fs::File::open(path).await else {
+            return Ok(text(StatusCode::NOT_FOUND, "not found"));
+        };
+        let length = file.metadata().await.map_err(|e| e.to_string())?.len();
+
+        let range = request
+            .headers()
+            .get(RANGE)
+            .and_then(|range| range.to_str().ok())
+            .and_then(|range| parse_range(range, length));
+        let (status, start, end) = match range {
+            Some(Some((start, end))) => (StatusCode::PARTIAL_CONTENT, start, end),
+            Some(None) => {
+                return Response::builder()
+                    .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                    .header(CONTENT_RANGE, format!("bytes */{length}"))
+                    .body(Empty::new().map_err(|e| match e {}).boxed_unsync())
+                    .map_err(|e| e.to_string());
+            }
+            None => (StatusCode::OK, 0, length.saturating_sub(1)),
+        };
+        let size = if length == 0 { 0 } else { end - start + 1 };
+
+        let mut response = Response::builder()
+            .status(status)
+            .header(ACCEPT_RANGES, "bytes")
+            .header(CONTENT_LENGTH, size)
+            .header(CONTENT_TYPE, "application/octet-stream");
+        if status == StatusCode::PARTIAL_CONTENT {
+            response = response.header(CONTENT_RANGE, format!("bytes {start}-{end}/{length}"));
+        }
+        if request.method() == Method::HEAD {
+            return response
+                .body(Empty::new().map_err(|e| match e {}).boxed_unsync())
+                .map_err(|e| e.to_string());
+        }
+
+        file.seek(io::SeekFrom::Start(start))
+            .await
+            .map_err(|e| e.to_string())?;
+        let drop_at =
+            (size > 0 && fastrand::f64() < self.args.drop_rate).then(|| fastrand::u64(0..size));
+        let rate = self.args.rate;
+
+        let body = stream::try_unfold((file, 0), move |(mut file, sent)| async move {
+            if sent >= size {
+                return Ok(None);
+            }
+            if drop_at.is_some_and(|drop_at| sent >= drop_at) {
+                return Err(io::Error::new(
+                    io::ErrorKind::ConnectionReset,
+                    "simulated dropped connection",
+                ));
+            }
+
+            let mut chunk = vec![0; CHUNK_SIZE.min((size - sent) as usize)];
+            file.read_exact(&mut chunk).await?;
+            if let Some(rate) = rate {
+                tokio::time::sleep(Duration::from_secs_f64(chunk.len() as f64 / rate as f64)).await;
+            }
+            let sent = sent + chunk.len() as u64;
+            Ok(Some((Frame::data(Bytes::from(chunk)), (file, sent))))
+        });
+        response
+            .body(StreamBody::new(body).boxed_unsync())
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// The files of `dir` sorted by name, with their modification time and size.
+fn file_stamps(dir: &Path) -> io::Result<Vec<FileStamp>> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        files.push((entry.file_name(), metadata.modified()?, metadata.len()));
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Matches `path` against a route with `{{name}}` placeholder segments, returning the placeholder values.
+fn match_route(route: &str, path: &str) -> Option<HashMap<String, String>> {
+    let route = route.trim_matches('/').split('/').collect::<Vec<_>>();
+    let path = path.trim_matches('/').split('/').collect::<Vec<_>>();
+    if route.len() != path.len() {
+        return None;
+    }
+
+    let mut params = HashMap::new();
+    for (route, path) in route.into_iter().zip(path) {
+        match route
+            .strip_prefix("{{")
+            .and_then(|name| name.strip_suffix("}}"))
+        {
+            Some(name) => {
+                let value = percent_encoding::percent_decode_str(path).decode_utf8_lossy();
+                params.insert(name.to_string(), value.into_owned());
+            }
+            None if route == path => {}
+            None => return None,
+        }
+    }
+    Some(params)
+}
+
+/// Parses a single `bytes=` range into inclusive bounds, `Some(None)` meaning it cannot be satisfied.
+fn parse_range(range: &str, length: u64) -> Option<Option<(u64, u64)>> {
+    let (start, end) = range.strip_prefix("bytes=")?.split_once('-')?;
+    let (start, end) = match (start.trim(), end.trim()) {
+        ("", suffix) => {
+            let suffix = suffix.parse::<u64>().ok()?;
+            (length.saturating_sub(suffix), length.saturating_sub(1))
+        }
+        (start, "") => (start.parse().ok()?, length.saturating_sub(1)),
+        (start, end) => (
+            start.parse().ok()?,
+            end.parse::<u64>().ok()?.min(length.saturating_sub(1)),
+        ),
+    };
+    Some((start < length && start <= end).then_some((start, end)))
+}
+
+fn json(release: &RemoteRelease) -> Result<Response<Body>, String> {
+    let body = serde_json::to_vec_pretty(release).map_err(|e| e.to_string())?;
+    Response::builder()
+        .header(CONTENT_TYPE, "application/json")
+        .body(
+            Full::new(Bytes::from(body))
+                .map_err(|e| match e {})
+                .boxed_unsync(),
+        )
+        .map_err(|e| e.to_string())
+}
+
+fn text(status: StatusCode, message: &str) -> Response<Body> {
+    let mut response = Response::new(
+        Full::new(Bytes::from(message.to_string()))
+            .map_err(|e| match e {})
+            .boxed_unsync(),
+    );
+    *response.status_mut() = status;
+    response
+}
+
+fn empty(status: StatusCode) -> Response<Body> {
+    let mut response = Response::new(Empty::new().map_err(|e| match e {}).boxed_unsync());
+    *response.status_mut() = status;
+    response
+}
+
+/// Validates a request path, adding the leading `/` if it is missing.
+fn parse_path(path: &str) -> Result<String, String> {
+    let path = path.trim();
+    if path.trim_matches('/').is_empty() {
+        return Err("the path must not be empty".into());
+    }
+    if path.contains(['?', '#']) {
+        return Err(format!("`{path}` must not have a query or fragment"));
+    }
+    if path.starts_with('/') {
+        Ok(path.to_string())
+    } else {
+        Ok(format!("/{path}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_are_normalized() {
+        assert_eq!(parse_path("latest.json").unwrap(), "/latest.json");
+        assert_eq!(
+            parse_path("/update/{{target}}").unwrap(),
+            "/update/{{target}}"
+        );
+        assert!(parse_path("").is_err());
+        assert!(parse_path("/").is_err());
+        assert!(parse_path("/latest.json?v=1").is_err());
+        // a multibyte first character is kept whole
+        assert_eq!(parse_path("ü.json").unwrap(), "/ü.json");
+    }
+
+    #[test]
+    fn route_placeholders_are_matched() {
+        let route = "/update/{{target}}/{{current_version}}";
+        let params = match_route(route, "/update/linux-x86_64/1.0.0").unwrap();
+        assert_eq!(params["target"], "linux-x86_64");
+        assert_eq!(params["current_version"], "1.0.0");
+        assert!(match_route(route, "/update/linux-x86_64").is_none());
+        assert!(match_route(route, "/other/linux-x86_64/1.0.0").is_none());
+    }
+}
@@ -0,0 +1,200 @@
+//! Generates minisign keys and signs update packages into the `.sig` files the plugin verifies.
+//!
+//! ```text
+//! universal-updater-signer generate --output ~/.keys/app.key
+//! universal-updater-signer sign --private-key-path ~/.keys/app.key app.AppImage.tar.gz
+//! universal-updater-signer verify --public-key-path ~/.keys/app.key.pub app.AppImage.tar.gz
+//! ```
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use clap::{Args, Parser, Subcommand};
+use tauri_plugin_universal_updater::minisign;
+
+#[derive(Parser)]
+#[command(about = "Generates keys and signs update packages")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generates a keypair, printing the public key to use as the `pubkey` of the plugin configuration.
+    Generate {
+        /// File to write the secret key to, the public key is written next to it with a `.pub` extension.
+        #[arg(long, short)]
+        output: PathBuf,
+        /// Password to encrypt the secret key with, leave empty to store it unencrypted.
+        #[arg(
+            long,
+            short,
+            env = "UNIVERSAL_UPDATER_PRIVATE_KEY_PASSWORD",
+            default_value = ""
+        )]
+        password: String,
+        /// Overwrite existing key files.
+        #[arg(long)]
+        force: bool,
+    },
+    /// Signs files, writing the signature of each to a `.sig` file next to it.
+    Sign {
+        #[command(flatten)]
+        key: PrivateKey,
+        /// Password the secret key is encrypted with.
+        #[arg(
+            long,
+            short,
+            env = "UNIVERSAL_UPDATER_PRIVATE_KEY_PASSWORD",
+            default_value = ""
+        )]
+        password: String,
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Verifies files against their `.sig` file with the same code the plugin runs before installing.
+    Verify {
+        #[command(flatten)]
+        key: PublicKey,
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+}
+
+#[derive(Args)]
+#[group(required = true, multiple = false)]
+struct PrivateKey {
+    /// Secret key, as written by `generate`.
+    #[arg(long, short = 'k', env = "UNIVERSAL_UPDATER_PRIVATE_KEY")]
+    private_key: Option<String>,
+    /// File with the secret key.
+    #[arg(long, short = 'f')]
+    private_key_path: Option<PathBuf>,
+}
+
+#[derive(Args)]
+#[group(required = true, multiple = false)]
+struct PublicKey {
+    /// Public key, as in the `pubkey` of the plugin configuration.
+    #[arg(long, short = 'k', env = "UNIVERSAL_UPDATER_PUBLIC_KEY")]
+    public_key: Option<String>,
+    /// File with the public key.
+    #[arg(long, short = 'f')]
+    public_key_path: Option<PathBuf>,
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse().command) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(command: Command) -> Result<(), String> {
+    match command {
+        Command::Generate {
+            output,
+            password,
+            force,
+        } => {
+            let public_key_path = PathBuf::from(format!("{}.pub", output.display()));
+            for path in [&output, &public_key_path] {
+                if path.exists() && !force {
+                    return Err(format!(
+                        "`{}` already exists, pass `--force` to overwrite it",
+                        path.display()
+                    ));
+                }
+            }
+
+            let (secret_key, public_key) = minisign::generate()?;
+            if let Some(parent) = output.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("failed to create `{}`: {e}", parent.display()))?;
+            }
+            write(&output, &secret_key.encode(&password)?)?;
+            write(&public_key_path, &public_key.encode())?;
+
+            eprintln!("secret key written to `{}`", output.display());
+            eprintln!("public key written to `{}`", public_key_path.display());
+            println!("{}", public_key.encode());
+            Ok(())
+        }
+        Command::Sign {
+            key,
+            password,
+            files,
+        } => {
+            let key = match (key.private_key, key.private_key_path) {
+                (Some(key), _) => key,
+                (None, Some(path)) => read_to_string(&path)?,
+                (None, None) => unreachable!("clap requires one of the keys"),
+            };
+            let secret_key = minisign::SecretKey::decode(&key, &password)?;
+
+            for file in files {
+                let data = read(&file)?;
+                let file_name = file
+                    .file_name()
+                    .map(|name| name.to_string_lossy())
+                    .unwrap_or_default();
+                let signature = secret_key.sign(&data, &file_name);
+                let signature_path = signature_path(&file);
+                write(&signature_path, &signature)?;
+                eprintln!("signed `{}`", file.display());
+            }
+            Ok(())
+        }
+        Command::Verify { key, files } => {
+            let public_key = match (key.public_key, key.public_key_path) {
+                (Some(key), _) => key,
+                (None, Some(path)) => read_to_string(&path)?,
+                (None, None) => unreachable!("clap requires one of the keys"),
+            };
+
+            let mut failed = false;
+            for file in files {
+                let data = read(&file)?;
+                let signature = read_to_string(&signature_path(&file))?;
+                match tauri_plugin_universal_updater::verify_signature(
+                    &data,
+                    &signature,
+                    &public_key,
+                ) {
+                    Ok(()) => eprintln!("`{}`: valid", file.display()),
+                    Err(e) => {
+                        eprintln!("`{}`: {e}", file.display());
+                        failed = true;
+                    }
+                }
+            }
+            if failed {
+                return Err("signature verification failed".into());
+            }
+            Ok(())
+        }
+    }
+}
+
+fn signature_path(file: &Path) -> PathBuf {
+    PathBuf::from(format!("{}.sig", file.display()))
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, String> {
+    fs::read(path).map_err(|e| format!("failed to read `{}`: {e}", path.display()))
+}
+
+fn read_to_string(path: &Path) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| format!("failed to read `{}`: {e}", path.display()))
+}
+
+fn write(path: &Path, contents: &str) -> Result<(), String> {
+    fs::write(path, contents).map_err(|e| format!("failed to write `{}`: {e}", path.display()))
+}
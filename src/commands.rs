@@ -5,12 +5,13 @@ use tauri::{
 
 use crate::Update;
 
-use crate::{Result, UniversalUpdater, UniversalUpdaterExt};
-use std::time::Duration;
+use crate::{Result, UniversalUpdater, UniversalUpdaterExt, UpdateResult};
+use std::{path::PathBuf, time::Duration};
 
 use url::Url;
 
-#[derive(Debug, Serialize)]
+/// Progress of an update download and install.
+#[derive(Debug, Clone, Serialize)]
 #[serde(tag = "event", content = "data")]
 pub enum DownloadEvent {
     #[serde(rename_all = "camelCase")]
@@ -20,8 +21,34 @@ pub enum DownloadEvent {
     #[serde(rename_all = "camelCase")]
     Progress {
         chunk_length: usize,
+        /// Bytes downloaded so far.
+        downloaded: u64,
+        /// Total size of the download, if known.
+        total: Option<u64>,
+        /// Smoothed download rate.
+        bytes_per_second: u64,
+        /// Estimated seconds until the download finishes, if the total size is known.
+        eta_seconds: Option<u64>,
     },
     Finished,
+    /// The download is served by `url`, sent again when switching to another mirror.
+    Mirror {
+        url: Url,
+    },
+    /// The download was paused.
+    Paused,
+    /// The download was resumed.
+    Resumed,
+    /// The downloaded package is being verified.
+    Verifying,
+    /// The package is being extracted from its archive.
+    Extracting,
+    /// The update is being installed.
+    Installing,
+    /// The download or install failed.
+    Failed {
+        error: String,
+    },
 }
 
 #[derive(Serialize, Default)]
@@ -46,6 +73,7 @@ pub(crate) async fn check<R: Runtime>(
     timeout: Option<u64>,
     proxy: Option<String>,
     target: Option<String>,
+    background: Option<bool>,
 ) -> Result<Metadata> {
     let updater = state.inner();
 
@@ -53,6 +81,7 @@ pub(crate) async fn check<R: Runtime>(
     let mut metadata = Metadata::default();
 
     if let Some(update) = update {
+        update.set_background(background.unwrap_or_default());
         metadata.available = true;
         metadata.current_version.clone_from(&update.current_version);
         metadata.version.clone_from(&update.version);
@@ -109,39 +138,103 @@ pub(crate) async fn download_and_install<R: Runtime>(
     webview: Webview<R>,
     state: State<'_, UniversalUpdater<R>>,
     rid: ResourceId,
-    on_event: Channel,
+    on_event: Channel<DownloadEvent>,
 ) -> Result<()> {
     let updater = state.inner();
     let update = webview.resources_table().get::<Update>(rid)?;
 
     #[cfg(desktop)]
-    {
-        let mut first_chunk = true;
-        update
-            .download_and_install(
-                &updater.handle,
-                |chunk, length| {
-                    if first_chunk {
-                        first_chunk = !first_chunk;
-                        let _ = on_event.send(DownloadEvent::Started {
-                            content_length: length,
-                        });
-                    };
-                    let _ = on_event.send(DownloadEvent::Progress {
-                        chunk_length: chunk,
-                    });
-                },
-                || {
-                    let _ = on_event.send(DownloadEvent::Finished);
-                },
-            )
-            .await?;
-    }
+    update
+        .download_and_install(&updater.handle, |event| {
+            let _ = on_event.send(event);
+        })
+        .await?;
 
     #[cfg(mobile)]
     update
-        .download_and_install(&updater.handle, on_event, || println!("download finished"))
+        .download_and_install(&updater.handle, on_event, || {})
         .await?;
 
     Ok(())
 }
+
+#[tauri::command]
+pub(crate) async fn install_and_relaunch<R: Runtime>(
+    webview: Webview<R>,
+    state: State<'_, UniversalUpdater<R>>,
+    rid: ResourceId,
+    on_event: Channel<DownloadEvent>,
+) -> Result<()> {
+    download_and_install(webview, state.clone(), rid, on_event).await?;
+    state.relaunch()
+}
+
+#[tauri::command]
+pub(crate) async fn relaunch<R: Runtime>(app: AppHandle<R>) -> Result<()> {
+    app.state::<UniversalUpdater<R>>().relaunch()
+}
+
+#[tauri::command]
+pub(crate) async fn mark_update_successful<R: Runtime>(app: AppHandle<R>) -> Result<()> {
+    app.state::<UniversalUpdater<R>>().mark_update_successful()
+}
+
+#[tauri::command]
+pub(crate) async fn get_update_result<R: Runtime>(app: AppHandle<R>) -> Result<UpdateResult> {
+    Ok(app.state::<UniversalUpdater<R>>().update_result().clone())
+}
+
+#[tauri::command]
+pub(crate) async fn cancel<R: Runtime>(webview: Webview<R>, rid: ResourceId) -> Result<()> {
+    webview.resources_table().get::<Update>(rid)?.cancel();
+    Ok(())
+}
+
+#[tauri::command]
+pub(crate) async fn pause<R: Runtime>(webview: Webview<R>, rid: ResourceId) -> Result<()> {
+    webview.resources_table().get::<Update>(rid)?.pause();
+    Ok(())
+}
+
+#[tauri::command]
+pub(crate) async fn resume<R: Runtime>(webview: Webview<R>, rid: ResourceId) -> Result<()> {
+    webview.resources_table().get::<Update>(rid)?.resume();
+    Ok(())
+}
+
+#[tauri::command]
+pub(crate) async fn set_rate_limit<R: Runtime>(
+    webview: Webview<R>,
+    rid: ResourceId,
+    bytes_per_second: Option<u64>,
+) -> Result<()> {
+    webview
+        .resources_table()
+        .get::<Update>(rid)?
+        .set_rate_limit(bytes_per_second);
+    Ok(())
+}
+
+#[tauri::command]
+pub(crate) async fn install_from_file<R: Runtime>(
+    app: AppHandle<R>,
+    path: PathBuf,
+    version: String,
+    on_event: Channel<DownloadEvent>,
+) -> Result<()> {
+    #[cfg(desktop)]
+    {
+        let version = semver::Version::parse(version.trim_start_matches('v'))?;
+        app.state::<UniversalUpdater<R>>()
+            .install_from_file(path, version, |event| {
+                let _ = on_event.send(event);
+            })
+            .await
+    }
+
+    #[cfg(mobile)]
+    {
+        let _ = (app, path, version, on_event);
+        Err(crate::Error::UnsupportedOs)
+    }
+}
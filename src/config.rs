@@ -86,17 +86,194 @@ where
         .collect::<Vec<_>>())
 }
 
+/// GitHub Releases used as the update source instead of a release JSON endpoint.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GithubConfig {
+    /// Owner of the repository.
+    pub owner: String,
+    /// Name of the repository.
+    pub repo: String,
+    /// Base URL of the GitHub API, e.g. for GitHub Enterprise. Defaults to `https://api.github.com`.
+    #[serde(default = "default_github_api_url", alias = "api-url")]
+    pub api_url: Url,
+    /// Token sent with API requests, raising the rate limit.
+    #[serde(default)]
+    pub token: Option<String>,
+    /// Also consider releases marked as pre-release.
+    #[serde(default)]
+    pub prerelease: bool,
+}
+
+fn default_github_api_url() -> Url {
+    Url::parse("https://api.github.com").expect("valid GitHub API URL")
+}
+
+/// An S3 compatible bucket used as the update source instead of a release JSON endpoint.
+///
+/// Releases are expected under `<prefix><version>/`, holding the packages named like the Tauri bundler does and
+/// their `.sig` files.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct S3Config {
+    /// Endpoint of the S3 API, e.g. `https://s3.eu-west-1.amazonaws.com` or a MinIO server.
+    pub endpoint: Url,
+    pub bucket: String,
+    /// Key prefix the release folders are listed under, e.g. `releases/`.
+    #[serde(default)]
+    pub prefix: String,
+    /// Region used to sign requests. Defaults to `us-east-1`.
+    #[serde(default = "default_s3_region")]
+    pub region: String,
+    /// Address the bucket as `<endpoint>/<bucket>` instead of `<bucket>.<endpoint host>`, as MinIO expects.
+    #[serde(default, alias = "path-style")]
+    pub path_style: bool,
+    /// Access key of a private bucket. Requests are signed with SigV4 when set.
+    #[serde(default, alias = "access-key-id")]
+    pub access_key_id: Option<String>,
+    #[serde(default, alias = "secret-access-key")]
+    pub secret_access_key: Option<String>,
+    #[serde(default, alias = "session-token")]
+    pub session_token: Option<String>,
+}
+
+fn default_s3_region() -> String {
+    "us-east-1".into()
+}
+
+/// Verification of the release JSON from the `endpoint` against the `pubkey`, so that a compromised endpoint
+/// cannot hide updates or offer old versions.
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestSignatureConfig {
+    /// Reject release JSON without a valid signature. Otherwise an unsigned one is accepted with a warning.
+    #[serde(default)]
+    pub required: bool,
+    /// Response header carrying the signature of the release JSON, e.g. `X-Manifest-Signature`.
+    #[serde(default)]
+    pub header: Option<String>,
+    /// URL of the detached signature, used when no `header` is set.
+    /// Defaults to the endpoint URL with a `.sig` suffix, e.g. `https://example.com/latest.json.sig`.
+    #[serde(default)]
+    pub url: Option<Url>,
+}
+
+/// Retry policy for the manifest request and the package download.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetryConfig {
+    /// Total number of attempts, including the first one. `1` disables retrying.
+    #[serde(default = "default_max_attempts", alias = "max-attempts")]
+    pub max_attempts: u32,
+    /// Delay in milliseconds before the first retry, doubled on every following one.
+    #[serde(default = "default_base_delay", alias = "base-delay")]
+    pub base_delay: u64,
+    /// Fraction of the delay that is randomized, between `0` and `1`.
+    #[serde(default = "default_jitter")]
+    pub jitter: f64,
+    /// HTTP status codes worth retrying. Connection errors and timeouts are always retried.
+    #[serde(
+        default = "default_retryable_status_codes",
+        alias = "retryable-status-codes"
+    )]
+    pub retryable_status_codes: Vec<u16>,
+}
+
+fn default_max_attempts() -> u32 {
+    3
+}
+
+fn default_base_delay() -> u64 {
+    500
+}
+
+fn default_jitter() -> f64 {
+    0.2
+}
+
+fn default_retryable_status_codes() -> Vec<u16> {
+    vec![408, 429, 500, 502, 503, 504]
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: default_max_attempts(),
+            base_delay: default_base_delay(),
+            jitter: default_jitter(),
+            retryable_status_codes: default_retryable_status_codes(),
+        }
+    }
+}
+
 /// Updater configuration.
 #[derive(Debug, Clone, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct Config {
     #[serde(default)]
     pub endpoint: Option<UpdaterEndpoint>,
+    /// Check GitHub Releases for updates instead of `endpoint`.
+    #[serde(default)]
+    pub github: Option<GithubConfig>,
+    /// Check an S3 compatible bucket for updates instead of `endpoint`.
+    #[serde(default)]
+    pub s3: Option<S3Config>,
+    /// Base64 encoded minisign public key update packages are verified with.
+    ///
+    /// When set, every package must come with a valid signature.
+    #[serde(default)]
+    pub pubkey: Option<String>,
+    /// Verify the minisign signature of the release JSON with the `pubkey` before reading it.
+    ///
+    /// Only applies to the release JSON of the `endpoint`, setting it with another source fails the plugin setup.
+    #[serde(default, alias = "manifest-signature")]
+    pub manifest_signature: Option<ManifestSignatureConfig>,
+    /// Seconds after its `pub_date` a release JSON is considered stale, failing the check.
+    ///
+    /// Guards against a mirror freezing clients on old metadata. Release JSON can also set an earlier `expires` date.
+    #[serde(default, alias = "manifest-max-age")]
+    pub manifest_max_age: Option<u64>,
+    /// Reject release JSON older than the newest one seen before, by version and then `pub_date`,
+    /// instead of silently reporting no update.
+    ///
+    /// Requires a `required` `manifestSignature` and a `pubkey`, so only verified release JSON is remembered.
+    #[serde(default, alias = "prevent-manifest-rollback")]
+    pub prevent_manifest_rollback: bool,
+    /// Seconds a newly installed version has to call `mark_update_successful` after its first launch.
+    ///
+    /// When set, the previous package is kept on install and restored on the next launch if the first launch did
+    /// not confirm within the window. The failed version is then skipped by future checks.
+    #[serde(default, alias = "rollback-window")]
+    pub rollback_window: Option<u64>,
+    /// Keep partially downloaded packages and continue them with HTTP range requests
+    /// instead of starting over, e.g. after a cancelled download.
+    #[serde(default)]
+    pub resume: bool,
+    /// Maximum download rate in bytes per second for background updates, e.g. ones started by a schedule.
+    ///
+    /// Foreground downloads are not limited unless a limit is set on the update.
+    #[serde(default, alias = "background-download-limit")]
+    pub background_download_limit: Option<u64>,
+    /// Seconds without receiving data after which a download moves on to the next mirror. Defaults to 30.
+    #[serde(default, alias = "stall-timeout")]
+    pub stall_timeout: Option<u64>,
+    /// Number of concurrent range requests large packages are split into. Disabled by default.
+    ///
+    /// Falls back to a single stream if the server does not support range requests. A failed segmented
+    /// download starts over instead of being resumed.
+    #[serde(default, alias = "download-segments")]
+    pub download_segments: Option<u32>,
+    /// Minimum size in bytes of a package to be downloaded in segments. Defaults to 16 MiB.
+    #[serde(default, alias = "segment-min-size")]
+    pub segment_min_size: Option<u64>,
+    /// Retry policy for transient network errors.
+    #[serde(default)]
+    pub retry: RetryConfig,
 }
 
 /// A URL to an updater server.
 ///
-/// The URL must use the `https` scheme on production.
+/// The URL must use the `https` scheme on production, or `file` for a manifest on the local file system.
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct UpdaterEndpoint(pub Url);
 
@@ -114,13 +291,13 @@ impl<'de> Deserialize<'de> for UpdaterEndpoint {
         let url = Url::deserialize(deserializer)?;
         #[cfg(not(feature = "schema"))]
         {
-            if url.scheme() != "https" {
+            if url.scheme() != "https" && url.scheme() != "file" {
                 #[cfg(debug_assertions)]
                 eprintln!("[\x1b[33mWARNING\x1b[0m] The configured updater endpoint doesn't use `https` protocol. This is allowed in development but will fail in release builds.");
 
                 #[cfg(not(debug_assertions))]
                 return Err(serde::de::Error::custom(
-                    "The configured updater endpoint must use the `https` protocol.",
+                    "The configured updater endpoint must use the `https` or `file` protocol.",
                 ));
             }
         }
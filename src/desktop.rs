@@ -1,16 +1,56 @@
-use crate::{Config, Error, Result, Update};
+use crate::{
+    hooks::Hooks,
+    mirror, naming, patch,
+    progress::Progress,
+    rollback,
+    store::{PendingInstall, Store},
+    throttle::Throttle,
+    verify_signature, Builder, Config, DownloadEvent, Error, OnBeforeExit, ReleasePatch, Result,
+    RetryConfig, Update, UpdateResult, UpdateSource,
+};
 use futures_util::StreamExt;
+use http::{header::RANGE, StatusCode};
 use reqwest::ClientBuilder;
 use semver::Version;
-use tauri::{ipc::Channel, plugin::PluginApi, AppHandle, Runtime};
+use sha2::{Digest, Sha256};
+#[cfg(target_os = "linux")]
+use std::borrow::Cow;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use tauri::{plugin::PluginApi, AppHandle, Manager, Runtime};
+use tokio::io::AsyncWriteExt;
 use url::Url;
 
+/// Seconds without data after which a download switches to the next mirror.
+const DEFAULT_STALL_TIMEOUT: u64 = 30;
+/// Minimum size of a package to be downloaded in segments.
+const DEFAULT_SEGMENT_MIN_SIZE: u64 = 16 * 1024 * 1024;
+
 pub fn init<R: Runtime>(
     app: &AppHandle<R>,
     api: PluginApi<R, Config>,
+    builder: Builder,
 ) -> crate::Result<UniversalUpdater<R>> {
     let config = api.config().clone();
-    let endpoint = config.endpoint.ok_or(Error::EmptyEndpoints)?;
+    let current_version = app.package_info().version.clone();
+    let store = Store::new(match builder.data_dir {
+        Some(dir) => dir,
+        None => app.path().app_data_dir()?,
+    });
+    let source = crate::source::from_config(builder.source, &config, &store)?;
+    let rolled_back = match config.rollback_window {
+        Some(_) => rollback::check_pending(&store, &current_version)?,
+        None => false,
+    };
+    // the restored version reports the rollback once the app restarted into it
+    let update_result = if rolled_back {
+        UpdateResult::default()
+    } else {
+        store.take_update_result(&current_version)?
+    };
 
     let arch = get_updater_arch().ok_or(Error::UnsupportedArch)?;
     let (target, json_target) = {
@@ -20,107 +60,602 @@ pub fn init<R: Runtime>(
 
     Ok(UniversalUpdater {
         handle: app.clone(),
-        endpoint: endpoint.0.clone(),
-        current_version: app.package_info().version.clone(),
+        endpoint: config.endpoint.map(|endpoint| endpoint.0),
+        source,
+        current_version,
         arch,
         target,
         json_target,
+        on_before_exit: builder.on_before_exit,
+        hooks: builder.hooks,
+        package_path: builder.package_path,
+        store,
+        pubkey: config.pubkey,
+        rollback_window: config.rollback_window,
+        rolled_back,
+        manifest_max_age: config.manifest_max_age.map(Duration::from_secs),
+        prevent_manifest_rollback: config.prevent_manifest_rollback,
+        background_download_limit: config.background_download_limit,
+        resume: config.resume,
+        stall_timeout: Duration::from_secs(config.stall_timeout.unwrap_or(DEFAULT_STALL_TIMEOUT)),
+        segments: config.download_segments.unwrap_or(1).max(1),
+        segment_min_size: config.segment_min_size.unwrap_or(DEFAULT_SEGMENT_MIN_SIZE),
+        retry: config.retry,
+        update_result,
     })
 }
 pub struct UniversalUpdater<R: Runtime> {
     pub handle: AppHandle<R>,
-    pub endpoint: Url,
+    pub endpoint: Option<Url>,
+    pub(crate) source: Arc<dyn UpdateSource>,
     pub current_version: Version,
     arch: &'static str,
     target: String,
     pub json_target: String,
+    on_before_exit: Option<OnBeforeExit>,
+    pub(crate) hooks: Hooks,
+    /// The installed package set on the builder, see [`Builder::package_path`].
+    pub(crate) package_path: Option<PathBuf>,
+    pub(crate) store: Store,
+    pub(crate) pubkey: Option<String>,
+    pub(crate) rollback_window: Option<u64>,
+    /// Set if the previous package was restored on startup, the app then restarts once it is ready.
+    pub(crate) rolled_back: bool,
+    pub(crate) manifest_max_age: Option<Duration>,
+    pub(crate) prevent_manifest_rollback: bool,
+    pub(crate) background_download_limit: Option<u64>,
+    pub(crate) resume: bool,
+    pub(crate) retry: RetryConfig,
+    pub(crate) stall_timeout: Duration,
+    pub(crate) segments: u32,
+    pub(crate) segment_min_size: u64,
+    pub(crate) update_result: UpdateResult,
+}
+
+impl<R: Runtime> UniversalUpdater<R> {
+    /// Restarts the app with its original arguments, running the `on_before_exit` hook first.
+    pub fn relaunch(&self) -> ! {
+        if let Some(on_before_exit) = &self.on_before_exit {
+            on_before_exit();
+        }
+        self.handle.restart()
+    }
 }
 
 impl Update {
-    pub async fn download<R: Runtime, C: FnMut(usize, Option<u64>), D: FnOnce()>(
+    /// Downloads the update package, reporting progress through `on_event`.
+    ///
+    /// When the release provides a delta patch for the running version, the patch is downloaded and
+    /// applied to the installed package instead. Any failure while doing so falls back to the full package.
+    #[tracing::instrument(
+        name = "download",
+        skip_all,
+        fields(version = %self.version, url = %self.download_url, bytes = tracing::field::Empty)
+    )]
+    pub async fn download<R: Runtime, F: FnMut(DownloadEvent)>(
         &self,
         handle: &AppHandle<R>,
-        mut on_chunk: C,
-        on_download_finish: D,
+        mut on_event: F,
     ) -> Result<Vec<u8>> {
-        let mut request = ClientBuilder::new();
+        self.hooks.before_download(self);
 
-        let response = request
-            .build()?
-            .get(self.download_url.clone())
-            .send()
-            .await?;
+        let result = self.download_verified(handle, &mut on_event).await;
+        match &result {
+            Ok(bytes) => {
+                tracing::Span::current().record("bytes", bytes.len());
+                self.hooks.after_verify(self);
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "download failed");
+                on_event(DownloadEvent::Failed {
+                    error: e.to_string(),
+                });
+            }
+        }
 
-        if !response.status().is_success() {
-            return Err(Error::Network(format!(
-                "Download request failed with status: {}",
-                response.status()
-            )));
+        self.hooks.on_failure(self, result)
+    }
+
+    async fn download_verified<R: Runtime, F: FnMut(DownloadEvent)>(
+        &self,
+        handle: &AppHandle<R>,
+        on_event: &mut F,
+    ) -> Result<Vec<u8>> {
+        if let Some(patch) = &self.patch {
+            match self.download_patched(handle, patch, on_event).await {
+                Ok(bytes) => return Ok(bytes),
+                Err(e) => {
+                    tracing::warn!(error = %e, "delta update failed, downloading the full package")
+                }
+            }
         }
 
-        let content_length: Option<u64> = response
-            .headers()
-            .get("Content-Length")
-            .and_then(|value| value.to_str().ok())
-            .and_then(|value| value.parse().ok());
+        let bytes = self
+            .fetch(
+                &mirror::weighted_order(&self.mirrors),
+                &self.store.download_path(&format!("{}.part", self.version)),
+                on_event,
+            )
+            .await?;
+        on_event(DownloadEvent::Verifying);
+        self.verify(&bytes, self.sha256.as_deref(), self.signature.as_deref())?;
 
-        let mut buffer = Vec::new();
+        Ok(bytes)
+    }
 
-        let mut stream = response.bytes_stream();
-        let mut size = 0;
-        while let Some(chunk) = stream.next().await {
-            let chunk = chunk?;
-            let len = chunk.len();
-            let prev_size = size;
-            size = size + len;
-            on_chunk(chunk.len(), content_length);
-            println!("{prev_size} + {len} = {size}");
-            buffer.extend(chunk);
+    async fn download_patched<R: Runtime, F: FnMut(DownloadEvent)>(
+        &self,
+        handle: &AppHandle<R>,
+        patch: &ReleasePatch,
+        on_event: &mut F,
+    ) -> Result<Vec<u8>> {
+        let path = match &self.package_path {
+            Some(path) => path.clone(),
+            None => installed_package_path(handle)?,
+        };
+        let old = std::fs::read(path)?;
+        let diff = self
+            .fetch(
+                std::slice::from_ref(&patch.url),
+                &self
+                    .store
+                    .download_path(&format!("{}.patch.part", self.version)),
+                on_event,
+            )
+            .await?;
+        let bytes = patch::apply(patch.format, &old, &diff)?;
+        on_event(DownloadEvent::Verifying);
+        self.verify(&bytes, patch.sha256.as_deref(), patch.signature.as_deref())?;
+        Ok(bytes)
+    }
+
+    #[tracing::instrument(name = "verify", skip_all, fields(bytes = bytes.len()))]
+    fn verify(&self, bytes: &[u8], sha256: Option<&str>, signature: Option<&str>) -> Result<()> {
+        if let Some(pubkey) = &self.pubkey {
+            let signature = signature.ok_or(Error::MissingSignature)?;
+            verify_signature(bytes, signature, pubkey)?;
+        }
+
+        let Some(expected) = sha256 else {
+            return Ok(());
+        };
+
+        let actual = hex::encode(Sha256::digest(bytes));
+        if !actual.eq_ignore_ascii_case(expected) {
+            return Err(Error::ChecksumMismatch {
+                expected: expected.to_string(),
+                actual,
+            });
         }
-        on_download_finish();
 
-        Ok(buffer)
+        Ok(())
     }
 
+    /// Installs the downloaded update package.
+    ///
+    /// Fails with [`Error::InstallVetoed`] if the `on_before_install` hook refuses the install.
     pub fn install(&self, bytes: impl AsRef<[u8]>) -> Result<()> {
+        self.install_with_events(bytes.as_ref(), &mut |_| {})
+    }
+
+    #[tracing::instrument(
+        name = "install",
+        skip_all,
+        fields(version = %self.version, bytes = bytes.len())
+    )]
+    pub(crate) fn install_with_events<F: FnMut(DownloadEvent)>(
+        &self,
+        bytes: &[u8],
+        on_event: &mut F,
+    ) -> Result<()> {
+        let result = self
+            .hooks
+            .before_install(self)
+            .and_then(|_| self.install_inner(bytes, on_event));
+        self.record_install(&result);
+        match &result {
+            Ok(()) => self.hooks.after_install(self),
+            Err(e) => {
+                tracing::warn!(error = %e, "install failed");
+                on_event(DownloadEvent::Failed {
+                    error: e.to_string(),
+                });
+            }
+        }
+
+        self.hooks.on_failure(self, result)
+    }
+
+    #[cfg(target_os = "linux")]
+    fn install_inner<F: FnMut(DownloadEvent)>(&self, bytes: &[u8], on_event: &mut F) -> Result<()> {
+        let appimage = extract_appimage(bytes, on_event)?;
+        on_event(DownloadEvent::Installing);
+        self.install_appimage(&appimage)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn install_inner<F: FnMut(DownloadEvent)>(
+        &self,
+        _bytes: &[u8],
+        _on_event: &mut F,
+    ) -> Result<()> {
+        Err(Error::UnsupportedInstall)
+    }
+
+    /// Replaces the running AppImage, keeping the previous one as a backup until the install completes.
+    ///
+    /// The backup is kept afterwards if rollback is enabled, so it can be restored if the new version
+    /// never confirms it started.
+    #[cfg(target_os = "linux")]
+    fn install_appimage(&self, bytes: &[u8]) -> Result<()> {
+        if !infer::app::is_elf(bytes) {
+            return Err(Error::InvalidUpdaterFormat);
+        }
+
+        let path = self
+            .package_path
+            .clone()
+            .or_else(|| std::env::var_os("APPIMAGE").map(PathBuf::from))
+            .ok_or(Error::FailedToDetermineExtractPath)?;
+        let backup = rollback::backup_path(&path);
+        let permissions = std::fs::metadata(&path)?.permissions();
+
+        std::fs::rename(&path, &backup)?;
+        if let Err(e) =
+            std::fs::write(&path, bytes).and_then(|_| std::fs::set_permissions(&path, permissions))
+        {
+            let _ = std::fs::rename(&backup, &path);
+            return Err(e.into());
+        }
+
+        if self.keep_backup {
+            self.store.update(|data| {
+                data.pending.replace(PendingInstall {
+                    from_version: self.current_version.clone(),
+                    version: self.version.clone(),
+                    path,
+                    backup,
+                    launched_at: None,
+                });
+            })
+        } else {
+            let _ = std::fs::remove_file(backup);
+            Ok(())
+        }
+    }
+
+    /// Downloads the file served by `mirrors` to `path`, returning the downloaded bytes.
+    ///
+    /// Mirrors are tried in order, switching to the next one when a mirror fails or stalls and continuing from
+    /// the bytes already written where the next mirror supports range requests.
+    /// If resuming is enabled, an existing partial file is continued and kept if the download fails, otherwise
+    /// it is always removed.
+    #[tracing::instrument(name = "fetch", skip_all, fields(mirrors = mirrors.len()))]
+    async fn fetch<F: FnMut(DownloadEvent)>(
+        &self,
+        mirrors: &[Url],
+        path: &Path,
+        on_event: &mut F,
+    ) -> Result<Vec<u8>> {
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        let mut progress = None;
+        let mut result = Err(Error::Network("no URL to download from".into()));
+        for (index, url) in mirrors.iter().enumerate() {
+            on_event(DownloadEvent::Mirror { url: url.clone() });
+            // a previous mirror left a partial file behind
+            let resume = self.resume || index > 0;
+            result = self
+                .fetch_mirror(url, path, resume, &mut progress, on_event)
+                .await;
+            match &result {
+                Ok(()) | Err(Error::Cancelled) | Err(Error::Io(_)) => break,
+                Err(e) => tracing::warn!(%url, error = %e, "mirror failed"),
+            }
+        }
+
+        if let Err(e) = result {
+            if !self.resume {
+                let _ = tokio::fs::remove_file(path).await;
+            }
+            return Err(e);
+        }
+
+        let bytes = tokio::fs::read(path).await?;
+        let _ = tokio::fs::remove_file(path).await;
+        Ok(bytes)
+    }
+
+    /// Downloads `url` to `path`, in segments if enabled and the file is large enough.
+    ///
+    /// A failed segmented download is removed since it cannot be continued with a single range request.
+    async fn fetch_mirror<F: FnMut(DownloadEvent)>(
+        &self,
+        url: &Url,
+        path: &Path,
+        resume: bool,
+        progress: &mut Option<Progress>,
+        on_event: &mut F,
+    ) -> Result<()> {
+        if url.scheme() == "file" {
+            return copy_local_file(url, path, progress, on_event).await;
+        }
+
+        let partial = resume && file_len(path).await > 0;
+        if self.segments > 1 && !partial {
+            let client = ClientBuilder::new().build()?;
+            match self
+                .fetch_segmented(&client, url, path, progress, on_event)
+                .await
+            {
+                Ok(true) => return Ok(()),
+                Ok(false) => {}
+                Err(e) => {
+                    let _ = tokio::fs::remove_file(path).await;
+                    return Err(e);
+                }
+            }
+        }
+        self.fetch_to_file(url, path, resume, progress, on_event)
+            .await
+    }
+
+    /// Streams `url` into `path`, continuing an existing file if `resume` is set.
+    ///
+    /// Pausing closes the connection; resuming continues from the end of the file with a range request.
+    async fn fetch_to_file<F: FnMut(DownloadEvent)>(
+        &self,
+        url: &Url,
+        path: &Path,
+        resume: bool,
+        progress: &mut Option<Progress>,
+        on_event: &mut F,
+    ) -> Result<()> {
+        let mut offset = match tokio::fs::metadata(path).await {
+            Ok(metadata) if resume => metadata.len(),
+            _ => 0,
+        };
+        let mut paused = self.paused.subscribe();
+        let mut throttle = Throttle::new(self.rate_limit.subscribe());
+        let mut attempt = 1;
+
+        loop {
+            if *paused.borrow_and_update() {
+                on_event(DownloadEvent::Paused);
+                tokio::select! {
+                    _ = self.cancel_token.cancelled() => return Err(Error::Cancelled),
+                    _ = paused.wait_for(|paused| !paused) => {}
+                }
+                on_event(DownloadEvent::Resumed);
+                if let Some(progress) = progress {
+                    progress.resume();
+                }
+            }
+
+            let mut request = ClientBuilder::new().build()?.get(url.clone());
+            if offset > 0 {
+                request = request.header(RANGE, format!("bytes={offset}-"));
+            }
+
+            let response = tokio::select! {
+                _ = self.cancel_token.cancelled() => return Err(Error::Cancelled),
+                _ = tokio::time::sleep(self.stall_timeout) => {
+                    return Err(Error::Stalled { url: url.clone() })
+                }
+                response = request.send() => response,
+            };
+
+            let response = match response {
+                Ok(response) if response.status().is_success() => response,
+                // the partial file is already complete, or longer than the package now is
+                Ok(response)
+                    if offset > 0 && response.status() == StatusCode::RANGE_NOT_SATISFIABLE =>
+                {
+                    tracing::debug!(offset, "partial download is not resumable, restarting");
+                    tokio::fs::File::create(path).await?;
+                    offset = 0;
+                    continue;
+                }
+                Ok(response)
+                    if self.retry.is_retryable_status(response.status())
+                        && self.retry.should_retry(attempt) =>
+                {
+                    self.wait_retry(&mut attempt).await?;
+                    offset = file_len(path).await;
+                    continue;
+                }
+                Ok(response) => {
+                    return Err(Error::Network(format!(
+                        "Download request failed with status: {}",
+                        response.status()
+                    )))
+                }
+                Err(e) if self.retry.is_retryable(&e) && self.retry.should_retry(attempt) => {
+                    self.wait_retry(&mut attempt).await?;
+                    offset = file_len(path).await;
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            // the server may ignore the range and send the whole file again
+            let resumed = offset > 0 && response.status() == StatusCode::PARTIAL_CONTENT;
+            if !resumed {
+                offset = 0;
+            }
+
+            let content_length: Option<u64> = response
+                .headers()
+                .get("Content-Length")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse::<u64>().ok())
+                .map(|length| length + offset);
+
+            let mut file = if resumed {
+                tokio::fs::OpenOptions::new()
+                    .append(true)
+                    .open(path)
+                    .await?
+            } else {
+                tokio::fs::File::create(path).await?
+            };
+
+            let progress = match progress {
+                Some(progress) if resumed => progress,
+                _ => {
+                    on_event(DownloadEvent::Started { content_length });
+                    progress.insert(Progress::new(offset, content_length))
+                }
+            };
+
+            let mut stream = response.bytes_stream();
+            let mut size = offset as usize;
+            let interrupted = loop {
+                let chunk = tokio::select! {
+                    _ = self.cancel_token.cancelled() => return Err(Error::Cancelled),
+                    _ = paused.wait_for(|paused| *paused) => break Some(Interrupted::Paused),
+                    _ = tokio::time::sleep(self.stall_timeout) => break Some(Interrupted::Stalled),
+                    chunk = stream.next() => chunk,
+                };
+                let chunk = match chunk {
+                    Some(Ok(chunk)) => chunk,
+                    Some(Err(e)) => break Some(Interrupted::Failed(e)),
+                    None => break None,
+                };
+                attempt = 1;
+
+                let len = chunk.len();
+                size += len;
+                file.write_all(&chunk).await?;
+                on_event(progress.advance(len));
+                tracing::trace!(chunk = len, downloaded = size);
+
+                tokio::select! {
+                    _ = self.cancel_token.cancelled() => return Err(Error::Cancelled),
+                    _ = throttle.consume(len) => {}
+                }
+            };
+            file.flush().await?;
+
+            match interrupted {
+                None => break,
+                Some(Interrupted::Paused) => {}
+                Some(Interrupted::Stalled) => return Err(Error::Stalled { url: url.clone() }),
+                // the connection dropped, continue from what was written so far
+                Some(Interrupted::Failed(e))
+                    if self.retry.is_retryable(&e) && self.retry.should_retry(attempt) =>
+                {
+                    self.wait_retry(&mut attempt).await?;
+                }
+                Some(Interrupted::Failed(e)) => return Err(e.into()),
+            }
+            offset = file_len(path).await;
+        }
+        on_event(DownloadEvent::Finished);
+
         Ok(())
     }
 
-    pub async fn download_and_install<R: Runtime, C: FnMut(usize, Option<u64>), D: FnOnce()>(
+    /// Waits before retrying a failed request and moves on to the next attempt.
+    pub(crate) async fn wait_retry(&self, attempt: &mut u32) -> Result<()> {
+        tokio::select! {
+            _ = self.cancel_token.cancelled() => return Err(Error::Cancelled),
+            _ = self.retry.wait(*attempt) => {}
+        }
+        *attempt += 1;
+        Ok(())
+    }
+
+    /// Downloads and installs the update package, reporting every step through `on_event`.
+    pub async fn download_and_install<R: Runtime, F: FnMut(DownloadEvent)>(
         &self,
         handle: &AppHandle<R>,
-        on_chunk: C,
-        on_download_finish: D,
+        mut on_event: F,
     ) -> Result<()> {
-        let bytes = self.download(handle, on_chunk, on_download_finish).await?;
+        let bytes = self.download(handle, &mut on_event).await?;
+        self.install_with_events(&bytes, &mut on_event)
+    }
+}
 
-        Ok(())
+/// Why a response stream ended before the download completed.
+pub(crate) enum Interrupted {
+    Paused,
+    Stalled,
+    Failed(reqwest::Error),
+}
+
+/// Copies the package at a `file://` URL to `path`, reporting it like a download.
+async fn copy_local_file<F: FnMut(DownloadEvent)>(
+    url: &Url,
+    path: &Path,
+    progress: &mut Option<Progress>,
+    on_event: &mut F,
+) -> Result<()> {
+    let source = url
+        .to_file_path()
+        .map_err(|_| Error::InvalidFileLocation(url.to_string()))?;
+    let len = tokio::fs::copy(source, path).await?;
+
+    on_event(DownloadEvent::Started {
+        content_length: Some(len),
+    });
+    let progress = progress.insert(Progress::new(0, Some(len)));
+    on_event(progress.advance(len as usize));
+    on_event(DownloadEvent::Finished);
+    Ok(())
+}
+
+/// Length of the partially downloaded file at `path`, `0` if there is none.
+async fn file_len(path: &Path) -> u64 {
+    tokio::fs::metadata(path)
+        .await
+        .map(|metadata| metadata.len())
+        .unwrap_or(0)
+}
+
+/// Returns the AppImage from an updater `.tar.gz` bundle, or `bytes` itself if it is not an archive.
+#[cfg(target_os = "linux")]
+fn extract_appimage<'a, F: FnMut(DownloadEvent)>(
+    bytes: &'a [u8],
+    on_event: &mut F,
+) -> Result<Cow<'a, [u8]>> {
+    if infer::archive::is_gz(bytes) {
+        use std::io::Read;
+
+        on_event(DownloadEvent::Extracting);
+        let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(bytes));
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            if entry.path()?.extension() == Some(std::ffi::OsStr::new("AppImage")) {
+                let mut appimage = Vec::new();
+                entry.read_to_end(&mut appimage)?;
+                return Ok(Cow::Owned(appimage));
+            }
+        }
+        return Err(Error::BinaryNotFoundInArchive);
     }
+
+    Ok(Cow::Borrowed(bytes))
 }
 
-pub(crate) fn get_updater_target() -> Option<&'static str> {
-    if cfg!(target_os = "linux") {
-        Some("linux")
-    } else if cfg!(target_os = "macos") {
-        // TODO shouldn't this be macos instead?
-        Some("darwin")
-    } else if cfg!(target_os = "windows") {
-        Some("windows")
-    } else {
-        None
+/// Path of the currently installed package, used as the base file for delta patches.
+fn installed_package_path<R: Runtime>(handle: &AppHandle<R>) -> Result<PathBuf> {
+    #[cfg(target_os = "linux")]
+    if let Some(appimage) = &handle.env().appimage {
+        return Ok(PathBuf::from(appimage));
     }
+    #[cfg(not(target_os = "linux"))]
+    let _ = handle;
+
+    std::env::current_exe().map_err(Into::into)
+}
+
+pub(crate) fn get_updater_target() -> Option<&'static str> {
+    naming::updater_target(std::env::consts::OS)
 }
 
 pub(crate) fn get_updater_arch() -> Option<&'static str> {
-    if cfg!(target_arch = "x86") {
-        Some("i686")
-    } else if cfg!(target_arch = "x86_64") {
-        Some("x86_64")
-    } else if cfg!(target_arch = "arm") {
-        Some("armv7")
-    } else if cfg!(target_arch = "aarch64") {
-        Some("aarch64")
-    } else {
-        None
-    }
+    naming::updater_arch(std::env::consts::OS, std::env::consts::ARCH)
 }
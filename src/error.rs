@@ -1,5 +1,10 @@
-use serde::{Serialize, Serializer};
+use serde::{ser::SerializeStruct, Serialize, Serializer};
+use serde_json::{json, Value};
 use thiserror::Error;
+use url::Url;
+
+/// Maximum number of characters of a response body kept in an error.
+const MAX_BODY_LENGTH: usize = 1024;
 
 /// All errors that can occur while running the updater.
 #[derive(Debug, Error)]
@@ -8,6 +13,9 @@ pub enum Error {
     /// Endpoints are not sent.
     #[error("Updater does not have any endpoints set.")]
     EmptyEndpoints,
+    /// The plugin configuration combines options that cannot be applied together.
+    #[error("invalid updater configuration: {0}")]
+    InvalidConfig(String),
     /// IO errors.
     #[error(transparent)]
     Io(#[from] std::io::Error),
@@ -20,12 +28,62 @@ pub enum Error {
     /// Could not fetch a valid response from the server.
     #[error("Could not fetch a valid release JSON from the remote")]
     ReleaseNotFound,
+    /// The release JSON request failed before a response was received, e.g. on a connection or TLS error.
+    #[error("failed to fetch the release JSON from `{url}`: {}", error_chain(.source))]
+    CheckRequest {
+        url: Url,
+        #[source]
+        source: reqwest::Error,
+    },
+    /// The server answered the release JSON request with an error status.
+    #[error("the release JSON request to `{url}` failed with status {status}")]
+    CheckStatus {
+        url: Url,
+        status: u16,
+        /// Start of the response body.
+        body: String,
+    },
+    /// The release JSON could not be parsed.
+    #[error("invalid release JSON from `{url}`: {source}")]
+    InvalidRelease {
+        url: Url,
+        #[source]
+        source: serde_json::Error,
+    },
+    /// The release JSON has no signature but signed release JSON is required.
+    #[error("the release JSON from `{url}` is not signed")]
+    UnsignedManifest { url: Url },
+    /// The signature of the release JSON could not be verified.
+    #[error("invalid signature of the release JSON from `{url}`: {message}")]
+    InvalidManifestSignature { url: Url, message: String },
+    /// The release JSON is past its `expires` date or older than the configured `manifestMaxAge`.
+    #[error("the release JSON is stale{}", .expired_at.as_ref().map(|at| format!(", it expired at {at}")).unwrap_or_default())]
+    StaleManifest {
+        /// RFC 3339 date the release JSON expired at, `None` if it has no date to check.
+        expired_at: Option<String>,
+    },
+    /// The release JSON is older than one seen before, as when a mirror replays outdated metadata.
+    #[error("the release JSON offers version {version}, older than the previously seen {latest}")]
+    ManifestRollback { version: String, latest: String },
+    /// The persisted updater state could not be parsed.
+    #[error("the updater state in `{}` is corrupt: {source}", .path.display())]
+    InvalidStore {
+        path: std::path::PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+    /// An S3 bucket listing could not be parsed.
+    #[error("invalid S3 bucket listing from `{url}`: {message}")]
+    InvalidListing { url: Url, message: String },
     /// Unsupported app architecture.
     #[error("Unsupported application architecture, expected one of `x86`, `x86_64`, `arm` or `aarch64`.")]
     UnsupportedArch,
     /// Operating system is not supported.
     #[error("Unsupported OS, expected one of `linux`, `darwin` or `windows`.")]
     UnsupportedOs,
+    /// Installing updates is not implemented on this operating system.
+    #[error("Installing updates is not supported on this OS yet.")]
+    UnsupportedInstall,
     /// Failed to determine updater package extract path
     #[error("Failed to determine updater package extract path.")]
     FailedToDetermineExtractPath,
@@ -61,6 +119,33 @@ pub enum Error {
     BinaryNotFoundInArchive,
     #[error("invalid updater binary format")]
     InvalidUpdaterFormat,
+    /// The downloaded package does not match the checksum from the release JSON.
+    #[error("checksum mismatch, expected `{expected}` but got `{actual}`")]
+    ChecksumMismatch { expected: String, actual: String },
+    /// A delta patch could not be applied.
+    #[error("failed to apply delta patch: {0}")]
+    Patch(String),
+    /// The `on_before_install` hook refused the install.
+    #[error("update install vetoed: {0}")]
+    InstallVetoed(String),
+    /// The running version was confirmed after its rollback window, so it is still rolled back on the next launch.
+    #[error("version {version} was confirmed after its rollback window, it is rolled back on the next launch")]
+    RollbackWindowExpired { version: String },
+    /// A `file://` URL or local path that cannot be read from.
+    #[error("`{0}` is not a valid local file location")]
+    InvalidFileLocation(String),
+    /// The release does not provide a signature for the package but a public key is configured.
+    #[error("the update package is not signed but a public key is configured")]
+    MissingSignature,
+    /// Installing a local package requires a public key to verify it with.
+    #[error("installing a package from a file requires a configured `pubkey`")]
+    MissingPubkey,
+    /// The download did not receive any data for too long.
+    #[error("the download from `{url}` stalled")]
+    Stalled { url: Url },
+    /// The download was cancelled.
+    #[error("the download was cancelled")]
+    Cancelled,
     #[error(transparent)]
     Http(#[from] http::Error),
     #[error(transparent)]
@@ -70,13 +155,136 @@ pub enum Error {
     PluginInvoke(#[from] tauri::plugin::mobile::PluginInvokeError),
 }
 
+impl Error {
+    /// Stable identifier of the error kind, sent to JS as `code`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::EmptyEndpoints => "emptyEndpoints",
+            Self::InvalidConfig(_) => "invalidConfig",
+            Self::Io(_) => "io",
+            Self::Semver(_) => "semver",
+            Self::Serialization(_) => "serialization",
+            Self::ReleaseNotFound => "releaseNotFound",
+            Self::CheckRequest { .. } => "checkRequest",
+            Self::CheckStatus { .. } => "checkStatus",
+            Self::InvalidRelease { .. } => "invalidRelease",
+            Self::UnsignedManifest { .. } => "unsignedManifest",
+            Self::InvalidManifestSignature { .. } => "invalidManifestSignature",
+            Self::StaleManifest { .. } => "staleManifest",
+            Self::ManifestRollback { .. } => "manifestRollback",
+            Self::InvalidStore { .. } => "invalidStore",
+            Self::InvalidListing { .. } => "invalidListing",
+            Self::UnsupportedArch => "unsupportedArch",
+            Self::UnsupportedOs => "unsupportedOs",
+            Self::UnsupportedInstall => "unsupportedInstall",
+            Self::FailedToDetermineExtractPath => "failedToDetermineExtractPath",
+            Self::UrlParse(_) => "urlParse",
+            Self::Reqwest(_) => "network",
+            Self::TargetNotFound(_) => "targetNotFound",
+            Self::Network(_) => "network",
+            Self::Minisign(_) => "signature",
+            Self::Base64(_) => "signature",
+            Self::SignatureUtf8(_) => "signature",
+            #[cfg(all(target_os = "windows", feature = "zip"))]
+            Self::Extract(_) => "extract",
+            Self::TempDirNotOnSameMountPoint => "tempDirNotOnSameMountPoint",
+            Self::BinaryNotFoundInArchive => "binaryNotFoundInArchive",
+            Self::InvalidUpdaterFormat => "invalidUpdaterFormat",
+            Self::ChecksumMismatch { .. } => "checksumMismatch",
+            Self::Patch(_) => "patch",
+            Self::InstallVetoed(_) => "installVetoed",
+            Self::RollbackWindowExpired { .. } => "rollbackWindowExpired",
+            Self::InvalidFileLocation(_) => "invalidFileLocation",
+            Self::MissingSignature => "signature",
+            Self::MissingPubkey => "signature",
+            Self::Stalled { .. } => "stalled",
+            Self::Cancelled => "cancelled",
+            Self::Http(_) => "http",
+            Self::Tauri(_) => "tauri",
+            #[cfg(mobile)]
+            Self::PluginInvoke(_) => "pluginInvoke",
+        }
+    }
+
+    /// Data describing the error beyond its message, sent to JS as `details`.
+    pub fn details(&self) -> Option<Value> {
+        match self {
+            Self::CheckRequest { url, source } => Some(json!({
+                "url": url,
+                "cause": error_chain(source),
+            })),
+            Self::CheckStatus { url, status, body } => Some(json!({
+                "url": url,
+                "status": status,
+                "body": body,
+            })),
+            Self::InvalidRelease { url, source } => Some(json!({
+                "url": url,
+                "cause": source.to_string(),
+            })),
+            Self::UnsignedManifest { url } => Some(json!({ "url": url })),
+            Self::InvalidManifestSignature { url, message } => Some(json!({
+                "url": url,
+                "cause": message,
+            })),
+            Self::StaleManifest { expired_at } => Some(json!({ "expiredAt": expired_at })),
+            Self::ManifestRollback { version, latest } => Some(json!({
+                "version": version,
+                "latest": latest,
+            })),
+            Self::InvalidListing { url, message } => Some(json!({
+                "url": url,
+                "cause": message,
+            })),
+            Self::Reqwest(e) => Some(json!({
+                "url": e.url(),
+                "status": e.status().map(|status| status.as_u16()),
+                "cause": error_chain(e),
+            })),
+            Self::Stalled { url } => Some(json!({ "url": url })),
+            Self::TargetNotFound(target) => Some(json!({ "target": target })),
+            Self::ChecksumMismatch { expected, actual } => Some(json!({
+                "expected": expected,
+                "actual": actual,
+            })),
+            Self::RollbackWindowExpired { version } => Some(json!({ "version": version })),
+            _ => None,
+        }
+    }
+}
+
 impl Serialize for Error {
     fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        serializer.serialize_str(self.to_string().as_ref())
+        let mut error = serializer.serialize_struct("Error", 3)?;
+        error.serialize_field("code", self.code())?;
+        error.serialize_field("message", &self.to_string())?;
+        error.serialize_field("details", &self.details())?;
+        error.end()
+    }
+}
+
+/// Formats an error with all of its sources, which `reqwest` leaves out of its message.
+fn error_chain(error: &dyn std::error::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    message
+}
+
+/// Shortens a response body to keep errors readable.
+pub(crate) fn truncate_body(mut body: String) -> String {
+    if let Some((index, _)) = body.char_indices().nth(MAX_BODY_LENGTH) {
+        body.truncate(index);
+        body.push('…');
     }
+    body
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,198 @@
+use async_trait::async_trait;
+use futures_util::future::try_join_all;
+use http::{
+    header::{ACCEPT, AUTHORIZATION, USER_AGENT},
+    HeaderMap, HeaderValue,
+};
+use reqwest::{Client, ClientBuilder};
+use semver::Version;
+use serde::Deserialize;
+use time::OffsetDateTime;
+use url::Url;
+
+use crate::{
+    config::GithubConfig, naming, source::fetch_manifest, Error, ReleaseManifestPlatform,
+    RemoteRelease, Result, RetryConfig, UpdateSource,
+};
+
+/// A release as returned by the GitHub REST API.
+#[derive(Deserialize)]
+struct GithubRelease {
+    tag_name: String,
+    body: Option<String>,
+    published_at: Option<String>,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    assets: Vec<GithubAsset>,
+}
+
+#[derive(Deserialize)]
+struct GithubAsset {
+    name: String,
+    browser_download_url: Url,
+}
+
+/// Looks up releases of a GitHub repository, the source used for the `github` configuration.
+///
+/// Release assets are matched to `json_target` keys by their file name, using the names of the Tauri bundler,
+/// and their signature is read from a sibling asset with the `.sig` extension.
+#[derive(Debug, Clone)]
+pub struct GithubSource {
+    config: GithubConfig,
+    retry: RetryConfig,
+}
+
+impl GithubSource {
+    pub fn new(config: GithubConfig) -> Self {
+        Self {
+            config,
+            retry: RetryConfig::default(),
+        }
+    }
+
+    /// Sets the retry policy of the GitHub requests.
+    pub fn retry(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+}
+
+#[async_trait]
+impl UpdateSource for GithubSource {
+    async fn latest_release(
+        &self,
+        _target: &str,
+        _current_version: &Version,
+    ) -> Result<Option<RemoteRelease>> {
+        latest_release(&self.config, &self.retry).await
+    }
+}
+
+/// Fetches the newest release of the configured repository, mapping its assets to platforms.
+async fn latest_release(
+    config: &GithubConfig,
+    retry: &RetryConfig,
+) -> Result<Option<RemoteRelease>> {
+    let client = ClientBuilder::new().build()?;
+
+    let release = if config.prerelease {
+        let url = api_url(config, "releases")?;
+        let Some(body) = fetch_manifest(&client, &url, api_headers(config), retry).await? else {
+            return Ok(None);
+        };
+        let releases: Vec<GithubRelease> = serde_json::from_slice(&body)
+            .map_err(|source| Error::InvalidRelease { url, source })?;
+        releases
+            .into_iter()
+            .filter(|release| !release.draft)
+            .filter_map(|release| Some((parse_tag(&release.tag_name).ok()?, release)))
+            .max_by(|(a, _), (b, _)| a.cmp(b))
+            .map(|(_, release)| release)
+    } else {
+        let url = api_url(config, "releases/latest")?;
+        match fetch_manifest(&client, &url, api_headers(config), retry).await {
+            Ok(Some(body)) => Some(
+                serde_json::from_slice(&body)
+                    .map_err(|source| Error::InvalidRelease { url, source })?,
+            ),
+            // the repository has no published release yet
+            Ok(None) | Err(Error::CheckStatus { status: 404, .. }) => None,
+            Err(e) => return Err(e),
+        }
+    };
+    let Some(release) = release else {
+        return Ok(None);
+    };
+
+    let version = parse_tag(&release.tag_name)?;
+    let pub_date = release.published_at.as_deref().and_then(|date| {
+        OffsetDateTime::parse(date, &time::format_description::well_known::Rfc3339).ok()
+    });
+
+    let assets = &release.assets;
+    let packages = naming::select_packages(assets, |asset| &asset.name);
+    let signatures = try_join_all(
+        packages
+            .values()
+            .map(|asset| fetch_signature(&client, assets, asset, retry)),
+    )
+    .await?;
+
+    let platforms = packages
+        .into_iter()
+        .zip(signatures)
+        .map(|((target, asset), signature)| {
+            let platform = ReleaseManifestPlatform {
+                url: asset.browser_download_url.clone(),
+                urls: Vec::new(),
+                sha256: None,
+                signature,
+                patches: Vec::new(),
+            };
+            (target, platform)
+        })
+        .collect();
+
+    Ok(Some(RemoteRelease {
+        version,
+        notes: release.body.clone(),
+        pub_date,
+        expires: None,
+        platforms,
+    }))
+}
+
+fn api_url(config: &GithubConfig, path: &str) -> Result<Url> {
+    let mut base = config.api_url.clone();
+    // keep the path of GitHub Enterprise API URLs like `https://host/api/v3`
+    if !base.path().ends_with('/') {
+        base.set_path(&format!("{}/", base.path()));
+    }
+    Ok(base.join(&format!("repos/{}/{}/{path}", config.owner, config.repo))?)
+}
+
+fn api_headers(config: &GithubConfig) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        ACCEPT,
+        HeaderValue::from_static("application/vnd.github+json"),
+    );
+    headers.insert(USER_AGENT, user_agent());
+    if let Some(token) = &config.token {
+        if let Ok(value) = HeaderValue::from_str(&format!("Bearer {token}")) {
+            headers.insert(AUTHORIZATION, value);
+        }
+    }
+    headers
+}
+
+fn user_agent() -> HeaderValue {
+    HeaderValue::from_static(concat!(
+        env!("CARGO_PKG_NAME"),
+        "/",
+        env!("CARGO_PKG_VERSION")
+    ))
+}
+
+fn parse_tag(tag: &str) -> Result<Version> {
+    Ok(Version::parse(tag.trim_start_matches('v'))?)
+}
+
+/// Downloads the signature of `package` from its `.sig` sibling asset, if there is one.
+async fn fetch_signature(
+    client: &Client,
+    assets: &[GithubAsset],
+    package: &GithubAsset,
+    retry: &RetryConfig,
+) -> Result<Option<String>> {
+    let name = format!("{}.sig", package.name);
+    let Some(asset) = assets.iter().find(|asset| asset.name == name) else {
+        return Ok(None);
+    };
+
+    let mut headers = HeaderMap::new();
+    headers.insert(USER_AGENT, user_agent());
+    let signature = fetch_manifest(client, &asset.browser_download_url, headers, retry).await?;
+    Ok(signature.map(|signature| String::from_utf8_lossy(&signature).trim().to_string()))
+}
@@ -0,0 +1,56 @@
+use std::sync::Arc;
+
+use crate::{Error, Result, Update};
+
+/// Callback invoked with the update at a step of the install pipeline.
+pub type UpdateHook = Arc<dyn Fn(&Update) + Send + Sync + 'static>;
+/// Callback invoked before the update is installed. Returning `Err(reason)` aborts the install.
+pub type BeforeInstallHook =
+    Arc<dyn Fn(&Update) -> std::result::Result<(), String> + Send + Sync + 'static>;
+/// Callback invoked when any step of the install pipeline fails.
+pub type FailureHook = Arc<dyn Fn(&Update, &Error) + Send + Sync + 'static>;
+
+/// Lifecycle hooks registered on the plugin [`Builder`](crate::Builder).
+#[derive(Clone, Default)]
+pub(crate) struct Hooks {
+    pub(crate) before_download: Option<UpdateHook>,
+    pub(crate) after_verify: Option<UpdateHook>,
+    pub(crate) before_install: Option<BeforeInstallHook>,
+    pub(crate) after_install: Option<UpdateHook>,
+    pub(crate) on_failure: Option<FailureHook>,
+}
+
+impl Hooks {
+    pub(crate) fn before_download(&self, update: &Update) {
+        if let Some(hook) = &self.before_download {
+            hook(update);
+        }
+    }
+
+    pub(crate) fn after_verify(&self, update: &Update) {
+        if let Some(hook) = &self.after_verify {
+            hook(update);
+        }
+    }
+
+    pub(crate) fn before_install(&self, update: &Update) -> Result<()> {
+        match &self.before_install {
+            Some(hook) => hook(update).map_err(Error::InstallVetoed),
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) fn after_install(&self, update: &Update) {
+        if let Some(hook) = &self.after_install {
+            hook(update);
+        }
+    }
+
+    /// Runs the failure hook if `result` is an error, passing the result through.
+    pub(crate) fn on_failure<T>(&self, update: &Update, result: Result<T>) -> Result<T> {
+        if let (Some(hook), Err(error)) = (&self.on_failure, &result) {
+            hook(update, error);
+        }
+        result
+    }
+}
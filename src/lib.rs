@@ -1,35 +1,68 @@
-use http::StatusCode;
-use reqwest::ClientBuilder;
 use semver::Version;
-use serde::{de::Error as DeError, Deserialize, Serialize};
+use serde::{de::Error as DeError, ser::Error as SerError, Deserialize, Serialize};
 use tauri::{
     plugin::{Builder as PluginBuilder, TauriPlugin},
     Manager, Resource, Runtime,
 };
 use time::OffsetDateTime;
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
 use url::Url;
 
-use std::{collections::HashMap, str::FromStr};
+use std::{cmp::Ordering, collections::HashMap, path::PathBuf, str::FromStr, sync::Arc};
 
 pub use models::*;
 
+#[cfg(feature = "cli")]
+pub mod artifacts;
 mod commands;
 mod config;
 mod error;
+mod github;
+mod hooks;
+#[cfg(feature = "cli")]
+pub mod minisign;
+mod mirror;
 mod models;
+pub mod naming;
+mod retry;
+mod s3;
+mod signature;
+mod source;
+mod store;
 
-pub use config::Config;
+pub use commands::DownloadEvent;
+pub use config::{Config, GithubConfig, ManifestSignatureConfig, RetryConfig, S3Config};
 pub use error::{Error, Result};
+pub use github::GithubSource;
+pub use hooks::{BeforeInstallHook, FailureHook, UpdateHook};
+pub use mirror::Mirror;
+pub use s3::S3Source;
+pub use signature::verify_signature;
+pub use source::{ManifestSource, UpdateSource};
+
+use hooks::Hooks;
+use store::{InstallRecord, LatestRelease, Store};
 
 #[cfg(desktop)]
 mod desktop;
 #[cfg(mobile)]
 mod mobile;
+#[cfg(desktop)]
+mod patch;
+#[cfg(desktop)]
+mod progress;
+#[cfg(desktop)]
+mod rollback;
+#[cfg(desktop)]
+mod segments;
+#[cfg(desktop)]
+mod throttle;
 
 #[cfg(desktop)]
-use desktop::*;
+pub use desktop::UniversalUpdater;
 #[cfg(mobile)]
-use mobile::*;
+pub use mobile::UniversalUpdater;
 
 /// Extensions to [`tauri::App`], [`tauri::AppHandle`] and [`tauri::Window`] to access the universal-updater APIs.
 pub trait UniversalUpdaterExt<R: Runtime> {
@@ -42,33 +75,235 @@ impl<R: Runtime, T: Manager<R>> UniversalUpdaterExt<R> for T {
     }
 }
 
+/// Callback invoked right before the app exits to relaunch into the new version.
+pub type OnBeforeExit = Arc<dyn Fn() + Send + Sync + 'static>;
+
+/// Builder for the universal-updater plugin.
+#[derive(Default)]
+pub struct Builder {
+    on_before_exit: Option<OnBeforeExit>,
+    hooks: Hooks,
+    source: Option<Arc<dyn UpdateSource>>,
+    data_dir: Option<PathBuf>,
+    #[cfg(desktop)]
+    package_path: Option<PathBuf>,
+}
+
+impl Builder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the source releases are looked up in, replacing the `endpoint` and `github` configuration.
+    pub fn source<S: UpdateSource + 'static>(mut self, source: S) -> Self {
+        self.source.replace(Arc::new(source));
+        self
+    }
+
+    /// Sets the directory the updater keeps its state and partial downloads in, instead of the app data directory.
+    pub fn data_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.data_dir.replace(dir.into());
+        self
+    }
+
+    /// Sets the installed package that updates replace and delta patches apply to, instead of the running
+    /// AppImage.
+    #[cfg(desktop)]
+    pub fn package_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.package_path.replace(path.into());
+        self
+    }
+
+    /// Sets a hook that runs before the app exits to relaunch, e.g. to flush state to disk.
+    pub fn on_before_exit<F: Fn() + Send + Sync + 'static>(mut self, f: F) -> Self {
+        self.on_before_exit.replace(Arc::new(f));
+        self
+    }
+
+    /// Sets a hook that runs before the update package is downloaded.
+    pub fn on_before_download<F: Fn(&Update) + Send + Sync + 'static>(mut self, f: F) -> Self {
+        self.hooks.before_download.replace(Arc::new(f));
+        self
+    }
+
+    /// Sets a hook that runs once the downloaded package has been verified.
+    ///
+    /// Only runs on desktop, on mobile the package is downloaded and verified by the native installer.
+    pub fn on_after_verify<F: Fn(&Update) + Send + Sync + 'static>(mut self, f: F) -> Self {
+        self.hooks.after_verify.replace(Arc::new(f));
+        self
+    }
+
+    /// Sets a hook that runs before the update is installed.
+    ///
+    /// Returning `Err(reason)` vetoes the install, which then fails with [`Error::InstallVetoed`].
+    pub fn on_before_install<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&Update) -> std::result::Result<(), String> + Send + Sync + 'static,
+    {
+        self.hooks.before_install.replace(Arc::new(f));
+        self
+    }
+
+    /// Sets a hook that runs after the update has been installed.
+    pub fn on_after_install<F: Fn(&Update) + Send + Sync + 'static>(mut self, f: F) -> Self {
+        self.hooks.after_install.replace(Arc::new(f));
+        self
+    }
+
+    /// Sets a hook that runs when downloading, verifying or installing the update fails.
+    pub fn on_failure<F: Fn(&Update, &Error) + Send + Sync + 'static>(mut self, f: F) -> Self {
+        self.hooks.on_failure.replace(Arc::new(f));
+        self
+    }
+
+    pub fn build<R: Runtime>(self) -> TauriPlugin<R, Config> {
+        tracing::debug!("initializing universal-updater plugin");
+        PluginBuilder::new("universal-updater")
+            .invoke_handler(tauri::generate_handler![
+                commands::check,
+                commands::download_and_install,
+                commands::install_and_relaunch,
+                commands::relaunch,
+                commands::mark_update_successful,
+                commands::get_update_result,
+                commands::cancel,
+                commands::pause,
+                commands::resume,
+                commands::set_rate_limit,
+                commands::install_from_file
+            ])
+            .setup(move |app, api| {
+                #[cfg(mobile)]
+                let universal_updater = mobile::init(app, api, self)?;
+                #[cfg(desktop)]
+                let universal_updater = desktop::init(app, api, self)?;
+                app.manage(universal_updater);
+
+                // manage state so it is accessible by the commands
+                Ok(())
+            })
+            .on_event(|app, event| {
+                // restarting during setup would skip the shutdown of the app, so a rollback
+                // restarts into the restored package once the app is ready
+                #[cfg(desktop)]
+                if let tauri::RunEvent::Ready = event {
+                    if app
+                        .try_state::<UniversalUpdater<R>>()
+                        .is_some_and(|updater| updater.rolled_back)
+                    {
+                        app.restart();
+                    }
+                }
+                #[cfg(mobile)]
+                let _ = (app, event);
+            })
+            .build()
+    }
+}
+
 /// Initializes the plugin.
 pub fn init<R: Runtime>() -> TauriPlugin<R, Config> {
-    println!("Initializing universal-updater plugin");
-    PluginBuilder::new("universal-updater")
-        .invoke_handler(tauri::generate_handler![
-            commands::check,
-            commands::download_and_install
-        ])
-        .setup(|app, api| {
-            #[cfg(mobile)]
-            let universal_updater = mobile::init(app, api)?;
-            #[cfg(desktop)]
-            let universal_updater = desktop::init(app, api)?;
-            app.manage(universal_updater);
-
-            // manage state so it is accessible by the commands
-            Ok(())
-        })
-        .build()
+    Builder::new().build()
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(try_from = "InnerReleaseManifestPlatform")]
 pub struct ReleaseManifestPlatform {
     /// Download URL for the platform
     pub url: Url,
-    // Signature for the platform
-    //pub signature: String,
+    /// Mirrors serving the same package, tried in an order weighted at random on download.
+    ///
+    /// `url` may be left out of the release JSON when this is set, it then defaults to the first mirror.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub urls: Vec<Mirror>,
+    /// Base64 encoded minisign signature of the full package, required if a `pubkey` is configured.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// Hex encoded SHA-256 of the full package.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    /// Delta patches from previous versions to this release.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub patches: Vec<ReleasePatch>,
+}
+
+#[derive(Deserialize)]
+struct InnerReleaseManifestPlatform {
+    url: Option<Url>,
+    #[serde(default)]
+    urls: Vec<Mirror>,
+    #[serde(default)]
+    sha256: Option<String>,
+    #[serde(default)]
+    signature: Option<String>,
+    #[serde(default)]
+    patches: Vec<ReleasePatch>,
+}
+
+impl TryFrom<InnerReleaseManifestPlatform> for ReleaseManifestPlatform {
+    type Error = String;
+
+    fn try_from(platform: InnerReleaseManifestPlatform) -> std::result::Result<Self, String> {
+        let url = platform
+            .url
+            .or_else(|| platform.urls.first().map(|mirror| mirror.url.clone()))
+            .ok_or("missing field `url` or `urls`")?;
+        Ok(Self {
+            url,
+            urls: platform.urls,
+            sha256: platform.sha256,
+            signature: platform.signature,
+            patches: platform.patches,
+        })
+    }
+}
+
+impl ReleaseManifestPlatform {
+    /// All locations the package can be downloaded from, `url` included.
+    pub fn mirrors(&self) -> Vec<Mirror> {
+        let mut mirrors = self.urls.clone();
+        if !mirrors.iter().any(|mirror| mirror.url == self.url) {
+            mirrors.insert(0, Mirror::new(self.url.clone()));
+        }
+        mirrors
+    }
+
+    /// Returns the delta patch that applies on top of `version`, if any.
+    pub fn patch_from(&self, version: &Version) -> Option<&ReleasePatch> {
+        self.patches.iter().find(|p| &p.from == version)
+    }
+}
+
+/// Binary delta formats supported for patches.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PatchFormat {
+    /// A classic `BSDIFF40` patch.
+    Bsdiff,
+    /// A patch created with `zstd --patch-from`.
+    Zstd,
+}
+
+/// A binary patch turning the installed AppImage of an older version into the one of this release.
+///
+/// The patched file is the AppImage itself rather than the `.tar.gz` package, so it is checked against its own
+/// `sha256`, or `signature` if a `pubkey` is configured.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ReleasePatch {
+    /// Version the patch applies to.
+    #[serde(deserialize_with = "parse_version")]
+    pub from: Version,
+    /// Download URL for the patch.
+    pub url: Url,
+    /// Patch format.
+    pub format: PatchFormat,
+    /// Hex encoded SHA-256 of the patched AppImage.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    /// Base64 encoded minisign signature of the patched AppImage, required if a `pubkey` is configured.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -79,17 +314,34 @@ pub struct RemoteRelease {
     pub notes: Option<String>,
     /// Release date.
     pub pub_date: Option<OffsetDateTime>,
+    /// Date after which the release JSON is stale and rejected, so a mirror cannot serve it forever.
+    pub expires: Option<OffsetDateTime>,
     /// Release data.
     pub platforms: HashMap<String, ReleaseManifestPlatform>,
 }
 
 impl RemoteRelease {
-    pub fn download_url(&self, target: &str) -> Result<&Url> {
+    /// Parses a release JSON read from `url`, resolving relative package URLs against it.
+    pub fn from_manifest(bytes: &[u8], url: &Url) -> Result<Self> {
+        let parse = || {
+            let mut release = serde_json::from_slice(bytes)?;
+            resolve_relative_urls(&mut release, url);
+            serde_json::from_value(release)
+        };
+        parse().map_err(|source| Error::InvalidRelease {
+            url: url.clone(),
+            source,
+        })
+    }
+
+    pub fn platform(&self, target: &str) -> Result<&ReleaseManifestPlatform> {
         self.platforms
             .get(target)
-            .map_or(Err(Error::TargetNotFound(target.to_string())), |p| {
-                Ok(&p.url)
-            })
+            .ok_or_else(|| Error::TargetNotFound(target.to_string()))
+    }
+
+    pub fn download_url(&self, target: &str) -> Result<&Url> {
+        self.platform(target).map(|p| &p.url)
     }
 }
 
@@ -104,29 +356,100 @@ impl<'de> Deserialize<'de> for RemoteRelease {
             version: Version,
             notes: Option<String>,
             pub_date: Option<String>,
+            expires: Option<String>,
             platforms: HashMap<String, ReleaseManifestPlatform>,
         }
 
         let release = InnerRemoteRelease::deserialize(deserializer)?;
 
-        let pub_date = if let Some(date) = release.pub_date {
-            Some(
+        let parse_date = |field: &str, date: Option<String>| {
+            date.map(|date| {
                 OffsetDateTime::parse(&date, &time::format_description::well_known::Rfc3339)
-                    .map_err(|e| DeError::custom(format!("invalid value for `pub_date`: {e}")))?,
-            )
-        } else {
-            None
+                    .map_err(|e| DeError::custom(format!("invalid value for `{field}`: {e}")))
+            })
+            .transpose()
         };
 
         Ok(RemoteRelease {
             version: release.version,
             notes: release.notes,
-            pub_date,
+            pub_date: parse_date("pub_date", release.pub_date)?,
+            expires: parse_date("expires", release.expires)?,
             platforms: release.platforms,
         })
     }
 }
 
+impl Serialize for RemoteRelease {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        #[derive(Serialize)]
+        struct InnerRemoteRelease<'a> {
+            version: String,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            notes: Option<&'a str>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            pub_date: Option<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            expires: Option<String>,
+            platforms: &'a HashMap<String, ReleaseManifestPlatform>,
+        }
+
+        let format_date = |field: &str, date: Option<OffsetDateTime>| {
+            date.map(|date| date.format(&time::format_description::well_known::Rfc3339))
+                .transpose()
+                .map_err(|e| SerError::custom(format!("invalid value for `{field}`: {e}")))
+        };
+
+        InnerRemoteRelease {
+            version: self.version.to_string(),
+            notes: self.notes.as_deref(),
+            pub_date: format_date("pub_date", self.pub_date)?,
+            expires: format_date("expires", self.expires)?,
+            platforms: &self.platforms,
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Rewrites the package URLs of a release JSON given as paths relative to the manifest into absolute URLs.
+fn resolve_relative_urls(release: &mut serde_json::Value, base: &Url) {
+    fn resolve(url: &mut serde_json::Value, base: &Url) {
+        if let serde_json::Value::String(url) = url {
+            if let Err(url::ParseError::RelativeUrlWithoutBase) = Url::parse(url) {
+                if let Ok(resolved) = base.join(url) {
+                    *url = resolved.into();
+                }
+            }
+        }
+    }
+
+    let Some(platforms) = release
+        .get_mut("platforms")
+        .and_then(|platforms| platforms.as_object_mut())
+    else {
+        return;
+    };
+    for platform in platforms.values_mut() {
+        if let Some(url) = platform.get_mut("url") {
+            resolve(url, base);
+        }
+        for key in ["urls", "patches"] {
+            let Some(entries) = platform.get_mut(key).and_then(|e| e.as_array_mut()) else {
+                continue;
+            };
+            for entry in entries {
+                match entry.get_mut("url") {
+                    Some(url) => resolve(url, base),
+                    None => resolve(entry, base),
+                }
+            }
+        }
+    }
+}
+
 fn parse_version<'de, D>(deserializer: D) -> std::result::Result<Version, D::Error>
 where
     D: serde::Deserializer<'de>,
@@ -137,43 +460,253 @@ where
 }
 
 impl<R: Runtime> UniversalUpdater<R> {
+    #[tracing::instrument(
+        name = "check",
+        skip_all,
+        fields(target = %self.json_target, current_version = %self.current_version)
+    )]
     pub async fn check(&self) -> Result<Option<Update>> {
-        let url = self.endpoint.clone();
+        let remote_release = self
+            .source
+            .latest_release(&self.json_target, &self.current_version)
+            .await?;
+        let Some(remote_release) = remote_release else {
+            tracing::debug!("no update available");
+            return Ok(None);
+        };
+        self.check_freshness(&remote_release)?;
 
-        let mut request = ClientBuilder::new();
+        let should_update = remote_release.version > self.current_version
+            && !self
+                .store
+                .load()?
+                .skipped_versions
+                .contains(&remote_release.version.to_string());
+
+        tracing::debug!(
+            version = %remote_release.version,
+            should_update,
+            "fetched release JSON"
+        );
+
+        let update = if should_update {
+            let platform = remote_release.platform(&self.json_target)?;
+            Some(self.update_for(
+                remote_release.version.to_string(),
+                remote_release.pub_date,
+                remote_release.notes.clone(),
+                platform,
+            ))
+        } else {
+            None
+        };
 
-        let response = request.build()?.get(url).send().await;
+        Ok(update)
+    }
 
-        let remote_release = if let Ok(res) = response {
-            if res.status().is_success() {
-                if StatusCode::NO_CONTENT == res.status() {
-                    return Ok(None);
-                };
+    /// Rejects a stale release, and one older than the newest seen before if rollback protection is enabled.
+    ///
+    /// Rollback protection requires a verified release JSON signature, so the newest release remembered is
+    /// always one the source verified.
+    fn check_freshness(&self, release: &RemoteRelease) -> Result<()> {
+        let now = OffsetDateTime::now_utc();
+        let max_age_expiry = self
+            .manifest_max_age
+            .map(|max_age| release.pub_date.map(|date| date + max_age));
+        let expires_at = match (release.expires, max_age_expiry) {
+            (Some(expires), Some(Some(expiry))) => Some(Some(expires.min(expiry))),
+            (Some(expires), _) => Some(Some(expires)),
+            (None, expiry) => expiry,
+        };
+        match expires_at {
+            // a max age is configured but the release JSON has no date to check it against
+            Some(None) => return Err(Error::StaleManifest { expired_at: None }),
+            Some(Some(expires_at)) if expires_at <= now => {
+                return Err(Error::StaleManifest {
+                    expired_at: expires_at
+                        .format(&time::format_description::well_known::Rfc3339)
+                        .ok(),
+                })
+            }
+            _ => {}
+        }
+        if !self.prevent_manifest_rollback {
+            return Ok(());
+        }
 
-                serde_json::from_value::<RemoteRelease>(res.json().await?)
-                    .map_err(Into::<Error>::into)
-            } else {
-                Err(Error::ReleaseNotFound)
+        let pub_date = release.pub_date.map(|date| date.unix_timestamp());
+        self.store.update(|data| {
+            let latest = data.latest_release.as_ref().and_then(|latest| {
+                let version = Version::parse(&latest.version).ok()?;
+                Some((version, latest.pub_date))
+            });
+            if let Some((version, latest_pub_date)) = &latest {
+                let ordering =
+                    release
+                        .version
+                        .cmp(version)
+                        .then_with(|| match (pub_date, latest_pub_date) {
+                            (Some(pub_date), Some(latest_pub_date)) => {
+                                pub_date.cmp(latest_pub_date)
+                            }
+                            _ => Ordering::Equal,
+                        });
+                if ordering.is_lt() {
+                    return Err(Error::ManifestRollback {
+                        version: release.version.to_string(),
+                        latest: version.to_string(),
+                    });
+                }
+                if ordering.is_eq() {
+                    return Ok(());
+                }
             }
-        } else {
-            Err(Error::ReleaseNotFound)
-        }?;
 
-        let should_update = remote_release.version > self.current_version;
+            data.latest_release = Some(LatestRelease {
+                version: release.version.to_string(),
+                pub_date,
+            });
+            Ok(())
+        })?
+    }
 
-        let update = if should_update {
-            Some(Update {
-                version: remote_release.version.to_string(),
-                current_version: self.current_version.to_string(),
-                date: remote_release.pub_date,
-                body: remote_release.notes.clone(),
-                download_url: remote_release.download_url(&self.json_target)?.to_owned(),
+    /// Creates the update installing `platform` of a release.
+    fn update_for(
+        &self,
+        version: String,
+        date: Option<OffsetDateTime>,
+        body: Option<String>,
+        platform: &ReleaseManifestPlatform,
+    ) -> Update {
+        // a patch rebuilds the installed AppImage, only usable when the result can be verified
+        let patch = platform
+            .patch_from(&self.current_version)
+            .filter(|patch| {
+                cfg!(target_os = "linux")
+                    && match self.pubkey {
+                        Some(_) => patch.signature.is_some(),
+                        None => patch.sha256.is_some(),
+                    }
             })
-        } else {
-            None
+            .cloned();
+
+        Update {
+            version,
+            current_version: self.current_version.to_string(),
+            date,
+            body,
+            download_url: platform.url.clone(),
+            mirrors: platform.mirrors(),
+            sha256: platform.sha256.clone(),
+            signature: platform.signature.clone(),
+            patch,
+            hooks: self.hooks.clone(),
+            store: self.store.clone(),
+            #[cfg(target_os = "linux")]
+            keep_backup: self.rollback_window.is_some(),
+            #[cfg(desktop)]
+            package_path: self.package_path.clone(),
+            #[cfg(desktop)]
+            pubkey: self.pubkey.clone(),
+            #[cfg(desktop)]
+            resume: self.resume,
+            #[cfg(desktop)]
+            retry: self.retry.clone(),
+            #[cfg(desktop)]
+            stall_timeout: self.stall_timeout,
+            #[cfg(desktop)]
+            segments: self.segments,
+            #[cfg(desktop)]
+            segment_min_size: self.segment_min_size,
+            cancel_token: CancellationToken::new(),
+            paused: watch::channel(false).0,
+            rate_limit: watch::channel(None).0,
+            background_download_limit: self.background_download_limit,
+        }
+    }
+
+    /// Installs the update package at `path` as `version`, e.g. from removable media on an offline machine.
+    ///
+    /// The package goes through the same verification and install steps as a downloaded one. Its signature is read
+    /// from the `.sig` file next to it and always verified, so a `pubkey` must be configured.
+    #[cfg(desktop)]
+    pub async fn install_from_file<F: FnMut(DownloadEvent)>(
+        &self,
+        path: impl AsRef<std::path::Path>,
+        version: Version,
+        mut on_event: F,
+    ) -> Result<()> {
+        if self.pubkey.is_none() {
+            return Err(Error::MissingPubkey);
+        }
+        let path = std::fs::canonicalize(path)?;
+        let url = Url::from_file_path(&path)
+            .map_err(|_| Error::InvalidFileLocation(path.display().to_string()))?;
+
+        let mut signature_path = path.into_os_string();
+        signature_path.push(".sig");
+        let signature = match std::fs::read_to_string(signature_path) {
+            Ok(signature) => signature,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(Error::MissingSignature)
+            }
+            Err(e) => return Err(e.into()),
         };
 
-        Ok(update)
+        let platform = ReleaseManifestPlatform {
+            url,
+            urls: Vec::new(),
+            sha256: None,
+            signature: Some(signature),
+            patches: Vec::new(),
+        };
+        let update = self.update_for(version.to_string(), None, None, &platform);
+        let bytes = update.download(&self.handle, &mut on_event).await?;
+        update.install_with_events(&bytes, &mut on_event)
+    }
+
+    /// Result of the last update, set on the first launch after it.
+    pub fn update_result(&self) -> &UpdateResult {
+        &self.update_result
+    }
+
+    /// The update installed before this launch, if this is the first launch of the new version.
+    pub fn just_updated(&self) -> Option<&JustUpdated> {
+        self.update_result.just_updated.as_ref()
+    }
+
+    /// Confirms the running version started correctly after an update.
+    ///
+    /// When `rollbackWindow` is configured, this must be called within the window after the first launch of a new
+    /// version, otherwise the previous version is restored on the next launch. Fails with
+    /// [`Error::RollbackWindowExpired`] once the window is over.
+    pub fn mark_update_successful(&self) -> Result<()> {
+        let Some(window) = self.rollback_window else {
+            return Ok(());
+        };
+
+        let current_version = self.current_version.to_string();
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        self.store.update(|data| {
+            let Some(pending) = data
+                .pending
+                .as_ref()
+                .filter(|pending| pending.version == current_version)
+            else {
+                return Ok(());
+            };
+            let deadline = pending
+                .launched_at
+                .map(|launched_at| launched_at.saturating_add(window as i64));
+            if deadline.is_some_and(|deadline| now > deadline) {
+                return Err(Error::RollbackWindowExpired {
+                    version: current_version,
+                });
+            }
+            let _ = std::fs::remove_file(&pending.backup);
+            data.pending = None;
+            Ok(())
+        })?
     }
 }
 
@@ -183,6 +716,136 @@ pub struct Update {
     date: Option<OffsetDateTime>,
     body: Option<String>,
     download_url: Url,
+    mirrors: Vec<Mirror>,
+    signature: Option<String>,
+    sha256: Option<String>,
+    patch: Option<ReleasePatch>,
+    hooks: Hooks,
+    store: Store,
+    #[cfg(target_os = "linux")]
+    keep_backup: bool,
+    #[cfg(desktop)]
+    package_path: Option<PathBuf>,
+    #[cfg(desktop)]
+    pubkey: Option<String>,
+    #[cfg(desktop)]
+    resume: bool,
+    #[cfg(desktop)]
+    retry: RetryConfig,
+    #[cfg(desktop)]
+    stall_timeout: std::time::Duration,
+    #[cfg(desktop)]
+    segments: u32,
+    #[cfg(desktop)]
+    segment_min_size: u64,
+    cancel_token: CancellationToken,
+    paused: watch::Sender<bool>,
+    rate_limit: watch::Sender<Option<u64>>,
+    background_download_limit: Option<u64>,
+}
+
+impl Update {
+    /// The version currently running.
+    pub fn current_version(&self) -> &str {
+        &self.current_version
+    }
+
+    /// The version of the update.
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// The release date of the update.
+    pub fn date(&self) -> Option<&OffsetDateTime> {
+        self.date.as_ref()
+    }
+
+    /// The release notes of the update.
+    pub fn body(&self) -> Option<&str> {
+        self.body.as_deref()
+    }
+
+    /// The URL the full update package is downloaded from.
+    pub fn download_url(&self) -> &Url {
+        &self.download_url
+    }
+
+    /// All locations the full update package can be downloaded from.
+    pub fn mirrors(&self) -> &[Mirror] {
+        &self.mirrors
+    }
+
+    /// The base64 encoded signature of the full update package.
+    pub fn signature(&self) -> Option<&str> {
+        self.signature.as_deref()
+    }
+
+    /// Token cancelling the downloads of this update.
+    pub fn cancellation_token(&self) -> &CancellationToken {
+        &self.cancel_token
+    }
+
+    /// Cancels the update. A running or later download fails with [`Error::Cancelled`].
+    pub fn cancel(&self) {
+        self.cancel_token.cancel();
+    }
+
+    /// Pauses the running download, closing its connection until [`Self::resume`] is called.
+    ///
+    /// The download continues where it stopped using an HTTP range request. Only supported on desktop.
+    pub fn pause(&self) {
+        self.paused.send_replace(true);
+    }
+
+    /// Resumes a paused download.
+    pub fn resume(&self) {
+        self.paused.send_replace(false);
+    }
+
+    /// Whether the download of this update is paused.
+    pub fn is_paused(&self) -> bool {
+        *self.paused.borrow()
+    }
+
+    /// Limits the download rate to `bytes_per_second`, or removes the limit with `None`.
+    ///
+    /// Takes effect immediately on a running download. Only supported on desktop.
+    pub fn set_rate_limit(&self, bytes_per_second: Option<u64>) {
+        self.rate_limit.send_replace(bytes_per_second);
+    }
+
+    /// The current download rate limit in bytes per second.
+    pub fn rate_limit(&self) -> Option<u64> {
+        *self.rate_limit.borrow()
+    }
+
+    /// Marks the update as downloaded in the background, applying the configured
+    /// `backgroundDownloadLimit`, or as a foreground download without limit.
+    pub fn set_background(&self, background: bool) {
+        self.set_rate_limit(if background {
+            self.background_download_limit
+        } else {
+            None
+        });
+    }
+
+    /// Persists the outcome of an install attempt, reported on the next launch.
+    ///
+    /// A cancelled attempt is not a failure of the update, so it is not recorded.
+    fn record_install<T>(&self, result: &Result<T>) {
+        if let Err(Error::Cancelled) = result {
+            return;
+        }
+        let record = InstallRecord {
+            from_version: self.current_version.clone(),
+            version: self.version.clone(),
+            notes: self.body.clone(),
+            error: result.as_ref().err().map(ToString::to_string),
+        };
+        let _ = self.store.update(|data| {
+            data.last_install.replace(record);
+        });
+    }
 }
 
 impl Resource for Update {}
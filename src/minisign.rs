@@ -0,0 +1,231 @@
+//! Reading and writing the minisign key and signature formats, base64 encoded once more as the Tauri CLI does.
+//!
+//! Used by the command line tools to generate keys and sign update packages.
+
+use std::fmt::Write;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ed25519_compact::{KeyPair, Seed};
+use time::OffsetDateTime;
+
+const SIGNATURE_ALGORITHM: &[u8; 2] = b"Ed";
+/// Signature algorithm of signatures over the BLAKE2b-512 hash of the data.
+const PREHASHED_ALGORITHM: &[u8; 2] = b"ED";
+const KDF_ALGORITHM: &[u8; 2] = b"Sc";
+const NO_KDF_ALGORITHM: &[u8; 2] = b"\0\0";
+const CHECKSUM_ALGORITHM: &[u8; 2] = b"B2";
+/// Scrypt limits minisign encrypts secret keys with.
+const KDF_OPSLIMIT: u64 = 33_554_432;
+const KDF_MEMLIMIT: u64 = 1_073_741_824;
+
+/// Length of the key id, secret key and checksum, the part of the secret key that is encrypted.
+const KEYNUM_SK_LENGTH: usize = 8 + 64 + 32;
+const SECRET_KEY_LENGTH: usize = 2 + 2 + 2 + 32 + 8 + 8 + KEYNUM_SK_LENGTH;
+
+pub struct SecretKey {
+    key_id: [u8; 8],
+    key: ed25519_compact::SecretKey,
+}
+
+pub struct PublicKey {
+    key_id: [u8; 8],
+    key: ed25519_compact::PublicKey,
+}
+
+/// Generates a new keypair.
+pub fn generate() -> Result<(SecretKey, PublicKey), String> {
+    let mut key_id = [0; 8];
+    getrandom::getrandom(&mut key_id).map_err(|e| format!("failed to generate a key: {e}"))?;
+    let keypair = KeyPair::from_seed(Seed::generate());
+    Ok((
+        SecretKey {
+            key_id,
+            key: keypair.sk,
+        },
+        PublicKey {
+            key_id,
+            key: keypair.pk,
+        },
+    ))
+}
+
+impl SecretKey {
+    /// Decodes a secret key, decrypting it with `password` if it is encrypted.
+    pub fn decode(encoded: &str, password: &str) -> Result<Self, String> {
+        let bytes = decode_key_file(encoded)?;
+        if bytes.len() != SECRET_KEY_LENGTH || &bytes[..2] != SIGNATURE_ALGORITHM {
+            return Err("not a minisign secret key".into());
+        }
+        if &bytes[4..6] != CHECKSUM_ALGORITHM {
+            return Err("unsupported secret key checksum algorithm".into());
+        }
+
+        let mut keynum_sk = bytes[54..].to_vec();
+        match <&[u8; 2]>::try_from(&bytes[2..4]).unwrap() {
+            KDF_ALGORITHM => {
+                let salt = &bytes[6..38];
+                let opslimit = u64::from_le_bytes(bytes[38..46].try_into().unwrap());
+                let memlimit = u64::from_le_bytes(bytes[46..54].try_into().unwrap());
+                let stream = kdf(password, salt, opslimit, memlimit)?;
+                keynum_sk
+                    .iter_mut()
+                    .zip(stream)
+                    .for_each(|(byte, key)| *byte ^= key);
+            }
+            NO_KDF_ALGORITHM => {}
+            _ => return Err("unsupported secret key encryption".into()),
+        }
+
+        let key_id: [u8; 8] = keynum_sk[..8].try_into().unwrap();
+        let key = ed25519_compact::SecretKey::from_slice(&keynum_sk[8..72])
+            .map_err(|e| format!("invalid secret key: {e}"))?;
+        let secret_key = Self { key_id, key };
+        if secret_key.checksum()[..] != keynum_sk[72..] {
+            return Err("wrong password for the secret key".into());
+        }
+        Ok(secret_key)
+    }
+
+    /// Encodes the secret key, encrypted with `password` unless it is empty.
+    pub fn encode(&self, password: &str) -> Result<String, String> {
+        let mut keynum_sk = Vec::with_capacity(KEYNUM_SK_LENGTH);
+        keynum_sk.extend_from_slice(&self.key_id);
+        keynum_sk.extend_from_slice(self.key.as_ref());
+        keynum_sk.extend_from_slice(&self.checksum());
+
+        let mut salt = [0; 32];
+        let kdf_algorithm = if password.is_empty() {
+            NO_KDF_ALGORITHM
+        } else {
+            getrandom::getrandom(&mut salt)
+                .map_err(|e| format!("failed to encrypt the secret key: {e}"))?;
+            let stream = kdf(password, &salt, KDF_OPSLIMIT, KDF_MEMLIMIT)?;
+            keynum_sk
+                .iter_mut()
+                .zip(stream)
+                .for_each(|(byte, key)| *byte ^= key);
+            KDF_ALGORITHM
+        };
+
+        let mut bytes = Vec::with_capacity(SECRET_KEY_LENGTH);
+        bytes.extend_from_slice(SIGNATURE_ALGORITHM);
+        bytes.extend_from_slice(kdf_algorithm);
+        bytes.extend_from_slice(CHECKSUM_ALGORITHM);
+        bytes.extend_from_slice(&salt);
+        bytes.extend_from_slice(&KDF_OPSLIMIT.to_le_bytes());
+        bytes.extend_from_slice(&KDF_MEMLIMIT.to_le_bytes());
+        bytes.extend_from_slice(&keynum_sk);
+
+        let comment = if password.is_empty() {
+            "minisign secret key"
+        } else {
+            "minisign encrypted secret key"
+        };
+        Ok(encode_key_file(comment, &bytes))
+    }
+
+    /// Signs `data`, writing `file_name` and the current time in the trusted comment like the Tauri bundler.
+    pub fn sign(&self, data: &[u8], file_name: &str) -> String {
+        let hash = blake2b_simd::blake2b(data);
+        let signature = self.key.sign(hash.as_bytes(), None);
+
+        let trusted_comment = format!(
+            "timestamp:{}\tfile:{file_name}",
+            OffsetDateTime::now_utc().unix_timestamp()
+        );
+        let mut global_data = signature.to_vec();
+        global_data.extend_from_slice(trusted_comment.as_bytes());
+        let global_signature = self.key.sign(global_data, None);
+
+        let mut bytes = Vec::with_capacity(2 + 8 + 64);
+        bytes.extend_from_slice(PREHASHED_ALGORITHM);
+        bytes.extend_from_slice(&self.key_id);
+        bytes.extend_from_slice(signature.as_ref());
+
+        let mut file = String::new();
+        let _ = writeln!(
+            file,
+            "untrusted comment: signature from minisign secret key"
+        );
+        let _ = writeln!(file, "{}", STANDARD.encode(bytes));
+        let _ = writeln!(file, "trusted comment: {trusted_comment}");
+        let _ = writeln!(file, "{}", STANDARD.encode(global_signature.as_ref()));
+        STANDARD.encode(file)
+    }
+
+    fn checksum(&self) -> [u8; 32] {
+        let mut state = blake2b_simd::Params::new().hash_length(32).to_state();
+        state.update(SIGNATURE_ALGORITHM);
+        state.update(&self.key_id);
+        state.update(self.key.as_ref());
+        state.finalize().as_bytes().try_into().unwrap()
+    }
+}
+
+impl PublicKey {
+    pub fn encode(&self) -> String {
+        let mut bytes = Vec::with_capacity(2 + 8 + 32);
+        bytes.extend_from_slice(SIGNATURE_ALGORITHM);
+        bytes.extend_from_slice(&self.key_id);
+        bytes.extend_from_slice(self.key.as_ref());
+        let comment = format!(
+            "minisign public key {:016X}",
+            u64::from_le_bytes(self.key_id)
+        );
+        encode_key_file(&comment, &bytes)
+    }
+}
+
+fn encode_key_file(comment: &str, bytes: &[u8]) -> String {
+    STANDARD.encode(format!(
+        "untrusted comment: {comment}\n{}\n",
+        STANDARD.encode(bytes)
+    ))
+}
+
+fn decode_key_file(encoded: &str) -> Result<Vec<u8>, String> {
+    let file = STANDARD
+        .decode(encoded.trim())
+        .ok()
+        .and_then(|file| String::from_utf8(file).ok())
+        .ok_or("the key is not base64 encoded")?;
+    let key = file
+        .lines()
+        .find(|line| !line.starts_with("untrusted comment:") && !line.trim().is_empty())
+        .ok_or("the key file is empty")?;
+    STANDARD
+        .decode(key.trim())
+        .map_err(|e| format!("invalid key: {e}"))
+}
+
+/// Derives the key stream secret keys are encrypted with, picking the scrypt parameters from the limits like
+/// libsodium.
+fn kdf(
+    password: &str,
+    salt: &[u8],
+    opslimit: u64,
+    memlimit: u64,
+) -> Result<[u8; KEYNUM_SK_LENGTH], String> {
+    let opslimit = opslimit.max(32_768);
+    let r = 8;
+    let (log_n, p) = if opslimit < memlimit / 32 {
+        (log_n(opslimit / (r * 4)), 1)
+    } else {
+        let log_n = log_n(memlimit / (r * 128));
+        let max_rp = ((opslimit / 4) >> log_n).min(0x3fff_ffff);
+        (log_n, (max_rp / r) as u32)
+    };
+
+    // the output length is taken from `stream`, the one of the parameters is only used for password hashes
+    let params = scrypt::Params::new(log_n, r as u32, p, scrypt::Params::RECOMMENDED_LEN)
+        .map_err(|e| format!("invalid secret key encryption parameters: {e}"))?;
+    let mut stream = [0; KEYNUM_SK_LENGTH];
+    scrypt::scrypt(password.as_bytes(), salt, &params, &mut stream)
+        .map_err(|e| format!("failed to derive the secret key encryption: {e}"))?;
+    Ok(stream)
+}
+
+/// Smallest `log_n` with `2^log_n > max_n / 2`.
+fn log_n(max_n: u64) -> u8 {
+    (1..63).find(|n| 1 << n > max_n / 2).unwrap_or(63)
+}
@@ -0,0 +1,85 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use url::Url;
+
+/// A download location of a platform package.
+///
+/// Deserializes from a plain URL string or from `{ "url": "...", "weight": 2 }`.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct Mirror {
+    pub url: Url,
+    /// Relative share of downloads sent to this mirror, `0` only uses it when all others fail.
+    pub weight: u32,
+}
+
+impl Mirror {
+    pub fn new(url: Url) -> Self {
+        Self { url, weight: 1 }
+    }
+}
+
+impl<'de> Deserialize<'de> for Mirror {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum InnerMirror {
+            Url(Url),
+            Weighted {
+                url: Url,
+                #[serde(default = "default_weight")]
+                weight: u32,
+            },
+        }
+
+        Ok(match InnerMirror::deserialize(deserializer)? {
+            InnerMirror::Url(url) => Mirror::new(url),
+            InnerMirror::Weighted { url, weight } => Mirror { url, weight },
+        })
+    }
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+/// Orders `mirrors` for a download, each position being drawn at random according to the mirror weights.
+pub(crate) fn weighted_order(mirrors: &[Mirror]) -> Vec<Url> {
+    // weighted random sampling without replacement: sort by `u^(1/weight)`
+    let mut keyed = mirrors
+        .iter()
+        .map(|mirror| {
+            let key = match mirror.weight {
+                0 => -1.0,
+                weight => fastrand::f64().powf(1.0 / weight as f64),
+            };
+            (key, &mirror.url)
+        })
+        .collect::<Vec<_>>();
+    keyed.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+    keyed.into_iter().map(|(_, url)| url.clone()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_weight_mirrors_come_last() {
+        let mirror = |url: &str, weight| Mirror {
+            url: url.parse().unwrap(),
+            weight,
+        };
+        let mirrors = [
+            mirror("https://fallback.example.com/app.tar.gz", 0),
+            mirror("https://a.example.com/app.tar.gz", 1),
+            mirror("https://b.example.com/app.tar.gz", 5),
+        ];
+        for _ in 0..20 {
+            let order = weighted_order(&mirrors);
+            assert_eq!(order.len(), 3);
+            assert_eq!(order[2], mirrors[0].url);
+        }
+    }
+}
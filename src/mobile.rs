@@ -1,14 +1,18 @@
 use crate::commands::DownloadEvent;
 use semver::Version;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{sync::Arc, time::Duration};
 use tauri::{
     ipc::Channel,
     plugin::{PluginApi, PluginHandle},
-    AppHandle, Runtime,
+    AppHandle, Manager, Runtime,
 };
 use url::Url;
 
-use crate::{Config, Error, Result, Update};
+use crate::{
+    hooks::Hooks, mirror, naming, store::Store, Builder, Config, Error, OnBeforeExit, Result,
+    RetryConfig, Update, UpdateResult, UpdateSource,
+};
 
 #[cfg(target_os = "android")]
 const PLUGIN_IDENTIFIER: &str = "com.sphereso.updater";
@@ -19,9 +23,15 @@ tauri::ios_plugin_binding!(universal_updater_plugin);
 pub fn init<R: Runtime>(
     app: &AppHandle<R>,
     api: PluginApi<R, Config>,
+    builder: Builder,
 ) -> crate::Result<UniversalUpdater<R>> {
     let config = api.config().clone();
-    let endpoint = config.endpoint.ok_or(Error::EmptyEndpoints)?;
+    let store = Store::new(match builder.data_dir {
+        Some(dir) => dir,
+        None => app.path().app_data_dir()?,
+    });
+    let source = crate::source::from_config(builder.source, &config, &store)?;
+    let update_result = store.take_update_result(&app.package_info().version)?;
 
     #[cfg(target_os = "android")]
     let handle = api.register_android_plugin(PLUGIN_IDENTIFIER, "UniversalUpdaterPlugin")?;
@@ -35,26 +45,60 @@ pub fn init<R: Runtime>(
     };
 
     Ok(UniversalUpdater {
+        app: app.clone(),
         handle: handle,
-        endpoint: endpoint.0.clone(),
+        endpoint: config.endpoint.map(|endpoint| endpoint.0),
+        source,
         current_version: app.package_info().version.clone(),
         arch,
         target,
         json_target,
+        on_before_exit: builder.on_before_exit,
+        hooks: builder.hooks,
+        store,
+        pubkey: config.pubkey,
+        rollback_window: config.rollback_window,
+        manifest_max_age: config.manifest_max_age.map(Duration::from_secs),
+        prevent_manifest_rollback: config.prevent_manifest_rollback,
+        background_download_limit: config.background_download_limit,
+        retry: config.retry,
+        update_result,
     })
 }
 pub struct UniversalUpdater<R: Runtime> {
+    app: AppHandle<R>,
     pub handle: PluginHandle<R>,
-    pub endpoint: Url,
+    pub endpoint: Option<Url>,
+    pub(crate) source: Arc<dyn UpdateSource>,
     pub current_version: Version,
     arch: &'static str,
     target: String,
     pub json_target: String,
+    on_before_exit: Option<OnBeforeExit>,
+    pub(crate) hooks: Hooks,
+    pub(crate) store: Store,
+    pub(crate) pubkey: Option<String>,
+    pub(crate) rollback_window: Option<u64>,
+    pub(crate) manifest_max_age: Option<Duration>,
+    pub(crate) prevent_manifest_rollback: bool,
+    pub(crate) background_download_limit: Option<u64>,
+    pub(crate) retry: RetryConfig,
+    pub(crate) update_result: UpdateResult,
+}
+
+impl<R: Runtime> UniversalUpdater<R> {
+    /// Restarts the app with its original arguments, running the `on_before_exit` hook first.
+    pub fn relaunch(&self) -> ! {
+        if let Some(on_before_exit) = &self.on_before_exit {
+            on_before_exit();
+        }
+        self.app.restart()
+    }
 }
 
 #[derive(Serialize)]
 struct DownloadArgs {
-    channel: Channel,
+    channel: Channel<DownloadEvent>,
     url: Url,
 }
 
@@ -70,22 +114,35 @@ struct Progress {
 }
 
 impl Update {
+    #[tracing::instrument(
+        name = "download",
+        skip_all,
+        fields(version = %self.version, url = %self.download_url)
+    )]
     pub async fn download<R: Runtime, D: FnOnce()>(
         &self,
         handle: &PluginHandle<R>,
-        on_chunk: Channel,
+        on_chunk: Channel<DownloadEvent>,
         on_download_finish: D,
     ) -> Result<()> {
+        if self.cancellation_token().is_cancelled() {
+            return Err(Error::Cancelled);
+        }
+
         handle
             .run_mobile_plugin(
                 "download",
                 DownloadArgs {
                     channel: on_chunk,
-                    url: self.download_url.clone(),
+                    // the native download does not fail over, start from a weighted pick
+                    url: mirror::weighted_order(&self.mirrors)
+                        .into_iter()
+                        .next()
+                        .unwrap_or_else(|| self.download_url.clone()),
                 },
             )
             .map_err(Into::<Error>::into)?;
-        println!("Finished Download");
+        tracing::debug!("download finished");
         on_download_finish();
         /*let mut request = ClientBuilder::new();
 
@@ -133,27 +190,28 @@ impl Update {
     pub async fn download_and_install<R: Runtime, D: FnOnce()>(
         &self,
         handle: &PluginHandle<R>,
-        on_chunk: Channel,
+        on_chunk: Channel<DownloadEvent>,
         on_download_finish: D,
     ) -> Result<()> {
-        let _ = self.download(handle, on_chunk, on_download_finish).await?;
+        // the android plugin downloads and installs the package in a single step
+        self.hooks.before_download(self);
+        let result = match self.hooks.before_install(self) {
+            Ok(()) => self.download(handle, on_chunk, on_download_finish).await,
+            Err(e) => Err(e),
+        };
+        self.record_install(&result);
+        if result.is_ok() {
+            self.hooks.after_install(self);
+        }
 
-        Ok(())
+        self.hooks.on_failure(self, result)
     }
 }
 
 pub(crate) fn get_updater_target() -> Option<&'static str> {
-    if cfg!(target_os = "android") {
-        Some("android")
-    } else {
-        None
-    }
+    naming::updater_target(std::env::consts::OS)
 }
 
 pub(crate) fn get_updater_arch() -> Option<&'static str> {
-    if cfg!(target_os = "android") {
-        Some("universal")
-    } else {
-        None
-    }
+    naming::updater_arch(std::env::consts::OS, std::env::consts::ARCH)
 }
@@ -11,3 +11,35 @@ pub struct PingRequest {
 pub struct PingResponse {
   pub value: Option<String>,
 }
+
+/// Outcome of the last update, available on the first launch after it.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateResult {
+    /// Set when this is the first launch of a version installed by the updater.
+    pub just_updated: Option<JustUpdated>,
+    /// Set when the last install attempt failed.
+    pub failed: Option<FailedInstall>,
+}
+
+/// The update that was installed before this launch.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JustUpdated {
+    /// Version that was running before the update.
+    pub from: String,
+    /// Version that was installed.
+    pub to: String,
+    /// Release notes of the installed version.
+    pub notes: Option<String>,
+}
+
+/// An install attempt that failed.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailedInstall {
+    /// Version that failed to install.
+    pub version: String,
+    /// Why the install failed.
+    pub error: String,
+}
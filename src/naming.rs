@@ -0,0 +1,240 @@
+//! The package file names of the Tauri bundler and the `json_target` keys they are built for.
+
+use std::collections::HashMap;
+
+/// Package file name suffixes and the `target_os` they are built for, in order of preference.
+const PACKAGE_SUFFIXES: &[(&str, &str)] = &[
+    (".AppImage", "linux"),
+    (".AppImage.tar.gz", "linux"),
+    (".app.tar.gz", "macos"),
+    ("-setup.exe", "windows"),
+    (".nsis.zip", "windows"),
+    (".msi", "windows"),
+    (".msi.zip", "windows"),
+    (".apk", "android"),
+];
+
+/// `target_os` and `target_arch` values the updater supports, see [`updater_target`] and [`updater_arch`].
+const OSES: &[&str] = &["linux", "macos", "windows", "android"];
+const ARCHS: &[&str] = &["x86", "x86_64", "arm", "aarch64"];
+
+/// The `json_target` OS of a `target_os`, e.g. `darwin` for `macos`.
+pub(crate) fn updater_target(os: &str) -> Option<&'static str> {
+    match os {
+        "linux" => Some("linux"),
+        // TODO shouldn't this be macos instead?
+        "macos" => Some("darwin"),
+        "windows" => Some("windows"),
+        "android" => Some("android"),
+        _ => None,
+    }
+}
+
+/// The `json_target` architecture of a `target_arch` on `os`.
+///
+/// Android packages hold every ABI, so they are `universal` whatever the architecture.
+pub(crate) fn updater_arch(os: &str, arch: &str) -> Option<&'static str> {
+    match (os, arch) {
+        ("android", _) => Some("universal"),
+        (_, "x86") => Some("i686"),
+        (_, "x86_64") => Some("x86_64"),
+        (_, "arm") => Some("armv7"),
+        (_, "aarch64") => Some("aarch64"),
+        _ => None,
+    }
+}
+
+/// Whether `target` is a `json_target` key the updater checks for, e.g. `linux-x86_64`.
+pub fn is_target(target: &str) -> bool {
+    OSES.iter().any(|os| {
+        ARCHS.iter().any(|arch| {
+            updater_target(os)
+                .zip(updater_arch(os, arch))
+                .is_some_and(|(os, arch)| target == format!("{os}-{arch}"))
+        })
+    })
+}
+
+/// Parses a `json_target` given on the command line, see [`is_target`].
+pub fn parse_target(target: &str) -> Result<String, String> {
+    if is_target(target) {
+        Ok(target.to_string())
+    } else {
+        Err(format!(
+            "`{target}` is not a `json_target` like `linux-x86_64`"
+        ))
+    }
+}
+
+/// Picks the package among `assets` for every target, preferring packages naming their architecture and then
+/// the package formats listed first in [`PACKAGE_SUFFIXES`].
+///
+/// Packages whose name does not tell their architecture, like `MyApp.app.tar.gz`, are left out.
+pub fn select_packages<T, F>(assets: &[T], name: F) -> HashMap<String, &T>
+where
+    F: Fn(&T) -> &str,
+{
+    select_packages_with_targets(assets, name, &[])
+}
+
+/// Like [`select_packages`], using packages whose name does not tell their architecture for the `targets` of
+/// their OS, e.g. `MyApp.app.tar.gz` for `darwin-aarch64`.
+pub fn select_packages_with_targets<'a, T, F>(
+    assets: &'a [T],
+    name: F,
+    targets: &[String],
+) -> HashMap<String, &'a T>
+where
+    F: Fn(&T) -> &str,
+{
+    // (has no architecture, format preference) of the selected asset, lower is better
+    let mut selected: HashMap<String, ((bool, usize), &T)> = HashMap::new();
+    for asset in assets {
+        let Some((os, named_targets, preference)) = asset_targets(name(asset)) else {
+            continue;
+        };
+        let (asset_targets, rank) = if named_targets.is_empty() {
+            let explicit = targets
+                .iter()
+                .filter(|target| target.split_once('-').map(|(os, _)| os) == Some(os))
+                .cloned()
+                .collect::<Vec<_>>();
+            (explicit, (true, preference))
+        } else {
+            (named_targets, (false, preference))
+        };
+        for target in asset_targets {
+            match selected.get(&target) {
+                Some((selected_rank, _)) if *selected_rank <= rank => {}
+                _ => {
+                    selected.insert(target, (rank, asset));
+                }
+            }
+        }
+    }
+    selected
+        .into_iter()
+        .map(|(target, (_, asset))| (target, asset))
+        .collect()
+}
+
+/// Returns the `json_target` keys a package file applies to, or `None` if it is not an updater package.
+///
+/// The OS is derived from the package extension and the architecture from a name segment such as `amd64`,
+/// `x64`, `aarch64` or `armhf`, using the names of the Tauri bundler. The result is empty if the name does not
+/// tell the architecture.
+pub fn package_targets(name: &str) -> Option<Vec<String>> {
+    asset_targets(name).map(|(_, targets, _)| targets)
+}
+
+/// The `json_target` OS of a package file, or `None` if it is not an updater package.
+pub fn package_os(name: &str) -> Option<&'static str> {
+    asset_targets(name).map(|(os, _, _)| os)
+}
+
+/// The `json_target` OS and keys of a package, along with its format preference for [`select_packages`].
+fn asset_targets(name: &str) -> Option<(&'static str, Vec<String>, usize)> {
+    let (preference, stem, os) =
+        PACKAGE_SUFFIXES
+            .iter()
+            .enumerate()
+            .find_map(|(preference, (suffix, os))| {
+                name.strip_suffix(suffix)
+                    .map(|stem| (preference, stem, *os))
+            })?;
+    let target_os = updater_target(os)?;
+
+    let mut archs = asset_archs(stem).to_vec();
+    if archs.is_empty() && os == "android" {
+        archs.push("");
+    }
+    let mut targets = archs
+        .iter()
+        .filter_map(|arch| updater_arch(os, arch))
+        .map(|arch| format!("{target_os}-{arch}"))
+        .collect::<Vec<_>>();
+    targets.dedup();
+    Some((target_os, targets, preference))
+}
+
+/// The `target_arch` values named in a package file name.
+fn asset_archs(stem: &str) -> &'static [&'static str] {
+    let stem = stem.to_lowercase();
+    if stem.contains("x86_64") || stem.contains("x86-64") {
+        return &["x86_64"];
+    }
+    stem.split(['_', '-', '.'])
+        .find_map(|segment| match segment {
+            "amd64" | "x64" => Some(&["x86_64"][..]),
+            "i686" | "i386" | "x86" => Some(&["x86"][..]),
+            "aarch64" | "arm64" => Some(&["aarch64"][..]),
+            "armv7" | "armhf" | "armeabi" => Some(&["arm"][..]),
+            // a macOS universal binary runs on both
+            "universal" => Some(&["x86_64", "aarch64"][..]),
+            _ => None,
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn targets(name: &str) -> Vec<String> {
+        let mut targets = package_targets(name).unwrap();
+        targets.sort();
+        targets
+    }
+
+    #[test]
+    fn package_targets_from_bundler_names() {
+        assert_eq!(targets("app_1.2.0_amd64.AppImage.tar.gz"), ["linux-x86_64"]);
+        assert_eq!(targets("app_1.2.0_aarch64.AppImage"), ["linux-aarch64"]);
+        assert_eq!(targets("app_1.2.0_x64-setup.exe"), ["windows-x86_64"]);
+        assert_eq!(targets("app_1.2.0_x86_en-US.msi.zip"), ["windows-i686"]);
+        assert_eq!(
+            targets("app_universal.app.tar.gz"),
+            ["darwin-aarch64", "darwin-x86_64"]
+        );
+        assert_eq!(targets("app-release.apk"), ["android-universal"]);
+        assert!(package_targets("app_1.2.0_amd64.deb").is_none());
+    }
+
+    #[test]
+    fn packages_without_arch_need_a_target() {
+        assert!(targets("MyApp.app.tar.gz").is_empty());
+        assert_eq!(package_os("MyApp.app.tar.gz"), Some("darwin"));
+
+        let assets = ["MyApp.app.tar.gz", "app_1.2.0_amd64.AppImage"];
+        let selected = select_packages(&assets, |name| name);
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected["linux-x86_64"], &"app_1.2.0_amd64.AppImage");
+
+        let selected =
+            select_packages_with_targets(&assets, |name| name, &["darwin-aarch64".into()]);
+        assert_eq!(selected.len(), 2);
+        assert_eq!(selected["darwin-aarch64"], &"MyApp.app.tar.gz");
+    }
+
+    #[test]
+    fn select_prefers_named_arch_and_format() {
+        let assets = [
+            "app.AppImage",
+            "app_amd64.AppImage.tar.gz",
+            "app_amd64.AppImage",
+        ];
+        let selected = select_packages_with_targets(&assets, |name| name, &["linux-x86_64".into()]);
+        assert_eq!(selected["linux-x86_64"], &"app_amd64.AppImage");
+    }
+
+    #[test]
+    fn targets_match_the_updater() {
+        assert!(is_target("linux-x86_64"));
+        assert!(is_target("darwin-aarch64"));
+        assert!(is_target("windows-i686"));
+        assert!(is_target("android-universal"));
+        assert!(!is_target("darwin-universal"));
+        assert!(!is_target("linux-universal"));
+        assert!(!is_target("darwin-armv7x"));
+    }
+}
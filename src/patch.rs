@@ -0,0 +1,257 @@
+use std::io::Read;
+
+use bzip2::read::BzDecoder;
+
+use crate::{Error, PatchFormat, Result};
+
+/// How many times larger than the installed package a patched file may be.
+const MAX_GROWTH: u64 = 2;
+
+/// Headroom on top of [`MAX_GROWTH`], so small packages can still grow by a reasonable amount.
+const GROWTH_ALLOWANCE: u64 = 64 * 1024 * 1024;
+
+/// Largest file a patch may produce on top of `old`. Patches are applied before they can be verified, so their
+/// header is untrusted and must not be able to request arbitrarily large allocations.
+fn max_patched_size(old: &[u8]) -> u64 {
+    (old.len() as u64)
+        .saturating_mul(MAX_GROWTH)
+        .saturating_add(GROWTH_ALLOWANCE)
+}
+
+/// Applies a delta patch in the given format to `old`, returning the patched file.
+pub(crate) fn apply(format: PatchFormat, old: &[u8], patch: &[u8]) -> Result<Vec<u8>> {
+    match format {
+        PatchFormat::Bsdiff => bspatch(old, patch),
+        PatchFormat::Zstd => zstd_patch(old, patch),
+    }
+}
+
+/// Applies a patch produced by `zstd --patch-from=<old>`.
+fn zstd_patch(old: &[u8], patch: &[u8]) -> Result<Vec<u8>> {
+    let mut decoder = zstd::stream::read::Decoder::with_ref_prefix(patch, old)?;
+    // `--patch-from` raises the window log to cover the whole reference file.
+    decoder.window_log_max(31)?;
+
+    let max_size = max_patched_size(old);
+    let mut new = Vec::new();
+    decoder.take(max_size + 1).read_to_end(&mut new)?;
+    if new.len() as u64 > max_size {
+        return Err(Error::Patch("zstd patch exceeds the maximum size".into()));
+    }
+    Ok(new)
+}
+
+/// Applies a classic `BSDIFF40` patch, as produced by the `bsdiff` tool.
+fn bspatch(old: &[u8], patch: &[u8]) -> Result<Vec<u8>> {
+    const HEADER_LEN: usize = 32;
+
+    if patch.len() < HEADER_LEN || &patch[..8] != b"BSDIFF40" {
+        return Err(Error::Patch("invalid bsdiff header".into()));
+    }
+
+    let corrupt_header = || Error::Patch("corrupt bsdiff header".into());
+    let ctrl_len = usize::try_from(offtin(&patch[8..16])).map_err(|_| corrupt_header())?;
+    let diff_len = usize::try_from(offtin(&patch[16..24])).map_err(|_| corrupt_header())?;
+    let new_size = u64::try_from(offtin(&patch[24..32])).map_err(|_| corrupt_header())?;
+    if new_size > max_patched_size(old) {
+        return Err(Error::Patch(format!(
+            "bsdiff target size {new_size} exceeds the maximum size"
+        )));
+    }
+    let new_size = usize::try_from(new_size).map_err(|_| corrupt_header())?;
+
+    let ctrl_end = HEADER_LEN
+        .checked_add(ctrl_len)
+        .filter(|end| *end <= patch.len())
+        .ok_or_else(|| Error::Patch("truncated bsdiff control block".into()))?;
+    let diff_end = ctrl_end
+        .checked_add(diff_len)
+        .filter(|end| *end <= patch.len())
+        .ok_or_else(|| Error::Patch("truncated bsdiff diff block".into()))?;
+
+    let mut ctrl = BzDecoder::new(&patch[HEADER_LEN..ctrl_end]);
+    let mut diff = BzDecoder::new(&patch[ctrl_end..diff_end]);
+    let mut extra = BzDecoder::new(&patch[diff_end..]);
+
+    let mut new = Vec::new();
+    new.try_reserve_exact(new_size)
+        .map_err(|e| Error::Patch(format!("cannot allocate the bsdiff target: {e}")))?;
+    new.resize(new_size, 0);
+
+    let exceeds_target = || Error::Patch("bsdiff patch exceeds target size".into());
+    let overflow = || Error::Patch("corrupt bsdiff control block".into());
+    let mut old_pos: i64 = 0;
+    let mut new_pos: usize = 0;
+    let mut buf = [0u8; 8];
+
+    while new_pos < new_size {
+        let mut triple = [0i64; 3];
+        for value in triple.iter_mut() {
+            ctrl.read_exact(&mut buf)?;
+            *value = offtin(&buf);
+        }
+        let [add_len, copy_len, seek_len] = triple;
+
+        let add_len = usize::try_from(add_len).map_err(|_| overflow())?;
+        if add_len > new_size - new_pos {
+            return Err(exceeds_target());
+        }
+        diff.read_exact(&mut new[new_pos..new_pos + add_len])?;
+        // `add_len` is bounded by the target size, so it fits an `i64`
+        let old_end = old_pos.checked_add(add_len as i64).ok_or_else(overflow)?;
+        for (i, byte) in new[new_pos..new_pos + add_len].iter_mut().enumerate() {
+            let pos = old_pos + i as i64;
+            if pos >= 0 && (pos as u64) < old.len() as u64 {
+                *byte = byte.wrapping_add(old[pos as usize]);
+            }
+        }
+        new_pos += add_len;
+        old_pos = old_end;
+
+        let copy_len = usize::try_from(copy_len).map_err(|_| overflow())?;
+        if copy_len > new_size - new_pos {
+            return Err(exceeds_target());
+        }
+        extra.read_exact(&mut new[new_pos..new_pos + copy_len])?;
+        new_pos += copy_len;
+        old_pos = old_pos.checked_add(seek_len).ok_or_else(overflow)?;
+    }
+
+    Ok(new)
+}
+
+/// Decodes bsdiff's sign-magnitude little endian integer encoding.
+fn offtin(buf: &[u8]) -> i64 {
+    let mut value = (buf[7] & 0x7f) as i64;
+    for byte in buf[..7].iter().rev() {
+        value = (value << 8) | *byte as i64;
+    }
+    if buf[7] & 0x80 != 0 {
+        -value
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use bzip2::{write::BzEncoder, Compression};
+
+    use super::*;
+
+    fn offtout(value: i64) -> [u8; 8] {
+        let mut buf = value.unsigned_abs().to_le_bytes();
+        if value < 0 {
+            buf[7] |= 0x80;
+        }
+        buf
+    }
+
+    fn bzip2(data: &[u8]) -> Vec<u8> {
+        let mut encoder = BzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    /// Builds a `BSDIFF40` patch from its control triples and blocks.
+    fn bsdiff(new_size: i64, ctrl: &[(i64, i64, i64)], diff: &[u8], extra: &[u8]) -> Vec<u8> {
+        let ctrl = ctrl
+            .iter()
+            .flat_map(|(add, copy, seek)| [offtout(*add), offtout(*copy), offtout(*seek)])
+            .flatten()
+            .collect::<Vec<_>>();
+        let (ctrl, diff) = (bzip2(&ctrl), bzip2(diff));
+
+        let mut patch = b"BSDIFF40".to_vec();
+        patch.extend_from_slice(&offtout(ctrl.len() as i64));
+        patch.extend_from_slice(&offtout(diff.len() as i64));
+        patch.extend_from_slice(&offtout(new_size));
+        patch.extend(ctrl);
+        patch.extend(diff);
+        patch.extend(bzip2(extra));
+        patch
+    }
+
+    #[test]
+    fn bsdiff_round_trip() {
+        let old = b"hello world, version 1";
+        let new = b"hello world, version 2!";
+        // the first 21 bytes are diffed against `old`, the rest is copied from the extra block
+        let diff = new[..21]
+            .iter()
+            .zip(old.iter())
+            .map(|(new, old)| new.wrapping_sub(*old))
+            .collect::<Vec<_>>();
+        let patch = bsdiff(new.len() as i64, &[(21, 2, 0)], &diff, &new[21..]);
+
+        assert_eq!(apply(PatchFormat::Bsdiff, old, &patch).unwrap(), new);
+    }
+
+    #[test]
+    fn zstd_round_trip() {
+        let old = b"hello world, version 1".repeat(100);
+        let mut new = old.clone();
+        new.extend_from_slice(b"version 2");
+
+        let mut encoder =
+            zstd::stream::write::Encoder::with_ref_prefix(Vec::new(), 3, &old).unwrap();
+        encoder.write_all(&new).unwrap();
+        let patch = encoder.finish().unwrap();
+
+        assert_eq!(apply(PatchFormat::Zstd, &old, &patch).unwrap(), new);
+    }
+
+    #[test]
+    fn bsdiff_rejects_invalid_header() {
+        assert!(matches!(
+            apply(PatchFormat::Bsdiff, b"old", b"BSDIFF39"),
+            Err(Error::Patch(_))
+        ));
+    }
+
+    #[test]
+    fn bsdiff_rejects_huge_target_size() {
+        let patch = bsdiff(i64::MAX, &[], &[], &[]);
+        assert!(matches!(
+            apply(PatchFormat::Bsdiff, b"old", &patch),
+            Err(Error::Patch(_))
+        ));
+    }
+
+    #[test]
+    fn bsdiff_target_size_is_bounded_by_old_file() {
+        let old = vec![0; 1024];
+        let patch = bsdiff(max_patched_size(&old) as i64 + 1, &[], &[], &[]);
+        let Err(Error::Patch(message)) = apply(PatchFormat::Bsdiff, &old, &patch) else {
+            panic!("the target size was not rejected");
+        };
+        assert!(message.contains("exceeds the maximum size"), "{message}");
+    }
+
+    #[test]
+    fn bsdiff_rejects_overflowing_seek() {
+        let patch = bsdiff(4, &[(1, 1, i64::MAX), (1, 1, i64::MAX)], &[0, 0], b"ab");
+        assert!(matches!(
+            apply(PatchFormat::Bsdiff, b"old", &patch),
+            Err(Error::Patch(_))
+        ));
+    }
+
+    #[test]
+    fn bsdiff_rejects_blocks_exceeding_target() {
+        let patch = bsdiff(2, &[(1, 5, 0)], &[0], b"abcde");
+        assert!(matches!(
+            apply(PatchFormat::Bsdiff, b"old", &patch),
+            Err(Error::Patch(_))
+        ));
+    }
+
+    #[test]
+    fn bsdiff_fails_on_truncated_control_block() {
+        let mut patch = bsdiff(4, &[(2, 2, 0)], &[0, 0], b"ab");
+        patch.truncate(40);
+        assert!(apply(PatchFormat::Bsdiff, b"old", &patch).is_err());
+    }
+}
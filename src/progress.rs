@@ -0,0 +1,84 @@
+use std::time::{Duration, Instant};
+
+use crate::DownloadEvent;
+
+/// Minimum time between two samples of the download rate.
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(250);
+/// Weight of the newest sample in the smoothed download rate.
+const SMOOTHING: f64 = 0.3;
+
+/// Tracks the progress of a download and turns chunks into [`DownloadEvent::Progress`] events.
+pub(crate) struct Progress {
+    downloaded: u64,
+    resumed_from: u64,
+    total: Option<u64>,
+    started: Instant,
+    last_sample: Instant,
+    sampled_bytes: u64,
+    rate: Option<f64>,
+}
+
+impl Progress {
+    /// Starts tracking a download, `downloaded` being the bytes already on disk when resuming.
+    pub(crate) fn new(downloaded: u64, total: Option<u64>) -> Self {
+        let now = Instant::now();
+        Self {
+            downloaded,
+            resumed_from: downloaded,
+            total,
+            started: now,
+            last_sample: now,
+            sampled_bytes: 0,
+            rate: None,
+        }
+    }
+
+    /// Restarts rate sampling after the download was paused.
+    pub(crate) fn resume(&mut self) {
+        self.last_sample = Instant::now();
+        self.sampled_bytes = 0;
+    }
+
+    /// Records a downloaded chunk and returns the matching progress event.
+    pub(crate) fn advance(&mut self, chunk_length: usize) -> DownloadEvent {
+        self.downloaded += chunk_length as u64;
+        self.sampled_bytes += chunk_length as u64;
+
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_sample);
+        if elapsed >= SAMPLE_INTERVAL {
+            let sample = self.sampled_bytes as f64 / elapsed.as_secs_f64();
+            self.rate = Some(match self.rate {
+                Some(rate) => SMOOTHING * sample + (1.0 - SMOOTHING) * rate,
+                None => sample,
+            });
+            self.last_sample = now;
+            self.sampled_bytes = 0;
+        }
+
+        // until the first sample is taken, use the average rate since the start
+        let rate = self.rate.unwrap_or_else(|| {
+            let elapsed = now.duration_since(self.started).as_secs_f64();
+            if elapsed > 0.0 {
+                (self.downloaded - self.resumed_from) as f64 / elapsed
+            } else {
+                0.0
+            }
+        });
+
+        let eta_seconds = match self.total {
+            Some(total) if rate > 0.0 => {
+                Some((total.saturating_sub(self.downloaded) as f64 / rate).ceil() as u64)
+            }
+            _ => None,
+        };
+
+        DownloadEvent::Progress {
+            chunk_length,
+            downloaded: self.downloaded,
+            total: self.total,
+            bytes_per_second: rate as u64,
+            eta_seconds,
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use std::time::Duration;
+
+use http::StatusCode;
+use reqwest::{RequestBuilder, Response};
+
+use crate::RetryConfig;
+
+/// Upper bound of the backoff exponent, keeps the delay from overflowing.
+const MAX_EXPONENT: u32 = 16;
+
+impl RetryConfig {
+    /// Whether another attempt may follow attempt number `attempt`, counting from 1.
+    pub(crate) fn should_retry(&self, attempt: u32) -> bool {
+        attempt < self.max_attempts
+    }
+
+    /// Delay before the retry following attempt number `attempt`, counting from 1.
+    pub(crate) fn delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(MAX_EXPONENT);
+        let delay = self.base_delay.saturating_mul(1 << exponent) as f64;
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        // spread the delay over [delay * (1 - jitter), delay]
+        let delay = delay * (1.0 - jitter * fastrand::f64());
+        Duration::from_millis(delay as u64)
+    }
+
+    pub(crate) fn is_retryable_status(&self, status: StatusCode) -> bool {
+        self.retryable_status_codes.contains(&status.as_u16())
+    }
+
+    /// Whether a failed request is worth retrying.
+    pub(crate) fn is_retryable(&self, error: &reqwest::Error) -> bool {
+        match error.status() {
+            Some(status) => self.is_retryable_status(status),
+            None => {
+                error.is_connect() || error.is_timeout() || error.is_request() || error.is_body()
+            }
+        }
+    }
+
+    /// Sends `request`, retrying on transient failures.
+    pub(crate) async fn send(&self, request: RequestBuilder) -> reqwest::Result<Response> {
+        let mut attempt = 1;
+        loop {
+            let Some(next) = request.try_clone() else {
+                // streaming bodies cannot be sent twice
+                return request.send().await;
+            };
+            let response = next.send().await;
+            let retryable = match &response {
+                Ok(res) => self.is_retryable_status(res.status()),
+                Err(e) => self.is_retryable(e),
+            };
+            if !retryable || !self.should_retry(attempt) {
+                return response;
+            }
+            tracing::debug!(attempt, "request failed, retrying");
+            self.wait(attempt).await;
+            attempt += 1;
+        }
+    }
+
+    /// Waits before the retry following attempt number `attempt`.
+    pub(crate) async fn wait(&self, attempt: u32) {
+        let delay = self.delay(attempt);
+        tracing::debug!(
+            attempt,
+            delay_ms = delay.as_millis() as u64,
+            "waiting before retry"
+        );
+        tokio::time::sleep(delay).await
+    }
+}
@@ -0,0 +1,144 @@
+use std::fs;
+
+use semver::Version;
+use time::OffsetDateTime;
+
+use crate::{
+    store::{InstallRecord, Store},
+    Result,
+};
+
+/// Path the previous package is moved to while installing an update.
+#[cfg(target_os = "linux")]
+pub(crate) fn backup_path(path: &std::path::Path) -> std::path::PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".bak");
+    path.with_file_name(file_name)
+}
+
+/// Checks the install awaiting confirmation on startup, returning whether the previous package was restored.
+///
+/// The first launch of the new version is recorded. If the version is launched again while the install is still
+/// pending, that first launch never called
+/// [`mark_update_successful`](crate::UniversalUpdater::mark_update_successful) in time, e.g. because it crashed.
+/// The backup is then restored and the version is skipped. The caller must restart the app into the restored
+/// package, which is deferred until the app is ready since this runs during plugin setup.
+///
+/// A backup that cannot be restored is given up on, so the app keeps starting with the new version.
+pub(crate) fn check_pending(store: &Store, current_version: &Version) -> Result<bool> {
+    store.update(|data| {
+        let Some(pending) = data.pending.as_mut() else {
+            return false;
+        };
+
+        // the new version is not the one running, e.g. the package was replaced by hand
+        if pending.version != current_version.to_string() {
+            let _ = fs::remove_file(&pending.backup);
+            data.pending = None;
+            return false;
+        }
+
+        if pending.launched_at.is_none() {
+            pending.launched_at = Some(OffsetDateTime::now_utc().unix_timestamp());
+            return false;
+        }
+
+        let version = pending.version.clone();
+        let restored = fs::rename(&pending.backup, &pending.path);
+        let error = match &restored {
+            Ok(()) => {
+                format!("version {version} was not marked successful in time and was rolled back")
+            }
+            Err(e) => {
+                tracing::error!(%version, error = %e, "failed to restore the previous version");
+                format!(
+                    "version {version} was not marked successful in time but the previous version could not be restored: {e}"
+                )
+            }
+        };
+        data.last_install.replace(InstallRecord {
+            from_version: pending.from_version.clone(),
+            version: version.clone(),
+            notes: None,
+            error: Some(error),
+        });
+        data.pending = None;
+        if restored.is_ok() {
+            data.skipped_versions.push(version);
+        }
+        restored.is_ok()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::PendingInstall;
+
+    /// A store with the install of 1.1.0 over `path` pending, the previous package being in `path.bak`.
+    fn pending_install(dir: &std::path::Path) -> (Store, std::path::PathBuf, std::path::PathBuf) {
+        let (path, backup) = (dir.join("app"), dir.join("app.bak"));
+        fs::write(&path, b"new").unwrap();
+        fs::write(&backup, b"old").unwrap();
+
+        let store = Store::new(dir.to_path_buf());
+        store
+            .update(|data| {
+                data.pending = Some(PendingInstall {
+                    from_version: "1.0.0".into(),
+                    version: "1.1.0".into(),
+                    path: path.clone(),
+                    backup: backup.clone(),
+                    launched_at: None,
+                })
+            })
+            .unwrap();
+        (store, path, backup)
+    }
+
+    #[test]
+    fn unconfirmed_launch_rolls_back_on_the_next_one() {
+        let dir = tempfile::tempdir().unwrap();
+        let (store, path, _) = pending_install(dir.path());
+        let version = Version::new(1, 1, 0);
+
+        // the first launch only records itself, the second one finds it never confirmed
+        assert!(!check_pending(&store, &version).unwrap());
+        assert!(check_pending(&store, &version).unwrap());
+
+        assert_eq!(fs::read(&path).unwrap(), b"old");
+        let data = store.load().unwrap();
+        assert!(data.pending.is_none());
+        assert_eq!(data.skipped_versions, ["1.1.0"]);
+        assert!(data.last_install.unwrap().error.is_some());
+    }
+
+    #[test]
+    fn missing_backup_is_given_up_on() {
+        let dir = tempfile::tempdir().unwrap();
+        let (store, path, backup) = pending_install(dir.path());
+        let version = Version::new(1, 1, 0);
+        fs::remove_file(backup).unwrap();
+
+        assert!(!check_pending(&store, &version).unwrap());
+        assert!(!check_pending(&store, &version).unwrap());
+
+        assert_eq!(fs::read(&path).unwrap(), b"new");
+        let data = store.load().unwrap();
+        assert!(data.pending.is_none());
+        assert!(data.skipped_versions.is_empty());
+        assert!(data.last_install.unwrap().error.is_some());
+    }
+
+    #[test]
+    fn other_version_drops_the_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let (store, path, backup) = pending_install(dir.path());
+
+        assert!(!check_pending(&store, &Version::new(1, 2, 0)).unwrap());
+
+        assert_eq!(fs::read(&path).unwrap(), b"new");
+        assert!(!backup.exists());
+        assert!(store.load().unwrap().pending.is_none());
+    }
+}
@@ -0,0 +1,221 @@
+use std::{io::SeekFrom, ops::Range, path::Path};
+
+use futures_util::{future::try_join_all, StreamExt};
+use http::{
+    header::{CONTENT_RANGE, RANGE},
+    StatusCode,
+};
+use reqwest::Client;
+use tokio::{
+    io::{AsyncSeekExt, AsyncWriteExt},
+    sync::{mpsc, Mutex},
+};
+use url::Url;
+
+use crate::{
+    desktop::Interrupted, progress::Progress, throttle::Throttle, DownloadEvent, Error, Result,
+    Update,
+};
+
+impl Update {
+    /// Downloads `url` to `path` with concurrent range requests, aggregating their progress into `on_event`.
+    ///
+    /// Returns `Ok(false)` without downloading anything if the server does not support range requests or the
+    /// file is too small to be split, the caller then falls back to a single stream.
+    pub(crate) async fn fetch_segmented<F: FnMut(DownloadEvent)>(
+        &self,
+        client: &Client,
+        url: &Url,
+        path: &Path,
+        progress: &mut Option<Progress>,
+        on_event: &mut F,
+    ) -> Result<bool> {
+        let total = match self.probe_length(client, url).await? {
+            Some(total) if total >= self.segment_min_size => total,
+            _ => return Ok(false),
+        };
+        tracing::debug!(total, segments = self.segments, "downloading in segments");
+
+        tokio::fs::File::create(path).await?.set_len(total).await?;
+
+        let segments = self.segments as u64;
+        let segment_len = (total + segments - 1) / segments;
+        let throttle = Mutex::new(Throttle::new(self.rate_limit.subscribe()));
+        let (chunks_tx, mut chunks) = mpsc::unbounded_channel();
+        let downloads = (0..segments)
+            .map(|index| index * segment_len..((index + 1) * segment_len).min(total))
+            .filter(|range| !range.is_empty())
+            .map(|range| self.fetch_segment(client, url, path, range, &throttle, chunks_tx.clone()))
+            .collect::<Vec<_>>();
+        drop(chunks_tx);
+        let downloads = try_join_all(downloads);
+        tokio::pin!(downloads);
+
+        on_event(DownloadEvent::Started {
+            content_length: Some(total),
+        });
+        let progress = progress.insert(Progress::new(0, Some(total)));
+        let mut paused = self.paused.subscribe();
+
+        loop {
+            tokio::select! {
+                result = &mut downloads => {
+                    result?;
+                    break;
+                }
+                Some(len) = chunks.recv() => on_event(progress.advance(len)),
+                Ok(()) = paused.changed() => {
+                    if *paused.borrow_and_update() {
+                        on_event(DownloadEvent::Paused);
+                    } else {
+                        on_event(DownloadEvent::Resumed);
+                        progress.resume();
+                    }
+                }
+            }
+        }
+        while let Ok(len) = chunks.try_recv() {
+            on_event(progress.advance(len));
+        }
+        on_event(DownloadEvent::Finished);
+
+        Ok(true)
+    }
+
+    /// Requests the first byte of `url` to find out whether ranges are supported and how large the file is.
+    async fn probe_length(&self, client: &Client, url: &Url) -> Result<Option<u64>> {
+        let request = client.get(url.clone()).header(RANGE, "bytes=0-0");
+        let response = tokio::select! {
+            _ = self.cancel_token.cancelled() => return Err(Error::Cancelled),
+            response = request.send() => response,
+        };
+
+        // any failure is left to the single stream download and its retries
+        let Ok(response) = response else {
+            return Ok(None);
+        };
+        if response.status() != StatusCode::PARTIAL_CONTENT {
+            return Ok(None);
+        }
+
+        // `bytes 0-0/<total>`
+        Ok(response
+            .headers()
+            .get(CONTENT_RANGE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.rsplit_once('/'))
+            .and_then(|(_, total)| total.parse().ok()))
+    }
+
+    /// Downloads the bytes in `range` into their place in `path`, sending the length of every chunk to `chunks`.
+    async fn fetch_segment(
+        &self,
+        client: &Client,
+        url: &Url,
+        path: &Path,
+        range: Range<u64>,
+        throttle: &Mutex<Throttle>,
+        chunks: mpsc::UnboundedSender<usize>,
+    ) -> Result<()> {
+        let mut file = tokio::fs::OpenOptions::new().write(true).open(path).await?;
+        let mut paused = self.paused.subscribe();
+        let mut position = range.start;
+        let mut attempt = 1;
+
+        while position < range.end {
+            if *paused.borrow_and_update() {
+                tokio::select! {
+                    _ = self.cancel_token.cancelled() => return Err(Error::Cancelled),
+                    _ = paused.wait_for(|paused| !paused) => {}
+                }
+            }
+
+            let request = client
+                .get(url.clone())
+                .header(RANGE, format!("bytes={position}-{}", range.end - 1));
+            let response = tokio::select! {
+                _ = self.cancel_token.cancelled() => return Err(Error::Cancelled),
+                _ = tokio::time::sleep(self.stall_timeout) => {
+                    return Err(Error::Stalled { url: url.clone() })
+                }
+                response = request.send() => response,
+            };
+
+            let response = match response {
+                Ok(response) if response.status() == StatusCode::PARTIAL_CONTENT => response,
+                Ok(response)
+                    if self.retry.is_retryable_status(response.status())
+                        && self.retry.should_retry(attempt) =>
+                {
+                    self.wait_retry(&mut attempt).await?;
+                    continue;
+                }
+                Ok(response) => {
+                    return Err(Error::Network(format!(
+                        "Segment request failed with status: {}",
+                        response.status()
+                    )))
+                }
+                Err(e) if self.retry.is_retryable(&e) && self.retry.should_retry(attempt) => {
+                    self.wait_retry(&mut attempt).await?;
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            file.seek(SeekFrom::Start(position)).await?;
+            let mut stream = response.bytes_stream();
+            let interrupted = loop {
+                let chunk = tokio::select! {
+                    _ = self.cancel_token.cancelled() => return Err(Error::Cancelled),
+                    _ = paused.wait_for(|paused| *paused) => break Some(Interrupted::Paused),
+                    _ = tokio::time::sleep(self.stall_timeout) => break Some(Interrupted::Stalled),
+                    chunk = stream.next() => chunk,
+                };
+                let chunk = match chunk {
+                    Some(Ok(chunk)) => chunk,
+                    Some(Err(e)) => break Some(Interrupted::Failed(e)),
+                    None => break None,
+                };
+                attempt = 1;
+
+                // never write into the next segment if the server sends more than requested
+                let len = chunk.len().min((range.end - position) as usize);
+                file.write_all(&chunk[..len]).await?;
+                position += len as u64;
+                let _ = chunks.send(len);
+
+                tokio::select! {
+                    _ = self.cancel_token.cancelled() => return Err(Error::Cancelled),
+                    _ = async { throttle.lock().await.consume(len).await } => {}
+                }
+                if position >= range.end {
+                    break None;
+                }
+            };
+            file.flush().await?;
+
+            match interrupted {
+                None if position >= range.end => {}
+                Some(Interrupted::Paused) => {}
+                Some(Interrupted::Stalled) => return Err(Error::Stalled { url: url.clone() }),
+                Some(Interrupted::Failed(e))
+                    if self.retry.is_retryable(&e) && self.retry.should_retry(attempt) =>
+                {
+                    self.wait_retry(&mut attempt).await?;
+                }
+                Some(Interrupted::Failed(e)) => return Err(e.into()),
+                // the response ended before the end of the segment
+                None if self.retry.should_retry(attempt) => self.wait_retry(&mut attempt).await?,
+                None => {
+                    return Err(Error::Network(format!(
+                        "Segment response ended at byte {position} of {}",
+                        range.end
+                    )))
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,18 @@
+use base64::Engine;
+use minisign_verify::{PublicKey, Signature};
+
+use crate::{Error, Result};
+
+/// Verifies `data` against a base64 encoded minisign signature, as written to `.sig` files by the Tauri bundler,
+/// using a base64 encoded minisign public key.
+pub fn verify_signature(data: &[u8], signature: &str, pubkey: &str) -> Result<()> {
+    let pubkey = PublicKey::decode(&base64_to_string(pubkey)?)?;
+    let signature = Signature::decode(&base64_to_string(signature)?)?;
+    pubkey.verify(data, &signature, true)?;
+    Ok(())
+}
+
+fn base64_to_string(value: &str) -> Result<String> {
+    let decoded = base64::engine::general_purpose::STANDARD.decode(value.trim())?;
+    String::from_utf8(decoded).map_err(|_| Error::SignatureUtf8(value.into()))
+}
@@ -0,0 +1,436 @@
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use http::{
+    header::{
+        AsHeaderName, AGE, CACHE_CONTROL, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+    },
+    HeaderMap, HeaderValue, StatusCode,
+};
+use reqwest::{Client, ClientBuilder, Response};
+use semver::Version;
+use time::OffsetDateTime;
+use url::Url;
+
+use crate::{
+    error,
+    store::{CachedManifest, Store},
+    verify_signature, Config, Error, GithubSource, ManifestSignatureConfig, RemoteRelease, Result,
+    RetryConfig, S3Source,
+};
+
+/// A backend releases are looked up in.
+///
+/// Register a custom source with [`Builder::source`](crate::Builder::source) to keep the plugin's download, verify
+/// and install steps for releases coming from elsewhere.
+#[async_trait]
+pub trait UpdateSource: Send + Sync {
+    /// Returns the latest release available for `target`, e.g. `linux-x86_64`, or `None` if there is none.
+    ///
+    /// The release is only offered as an update if it is newer than `current_version`.
+    async fn latest_release(
+        &self,
+        target: &str,
+        current_version: &Version,
+    ) -> Result<Option<RemoteRelease>>;
+}
+
+/// Reads the release JSON from an `https` or `file` URL, the source used for the configured `endpoint`.
+///
+/// The last release JSON is cached with its `ETag` and `Last-Modified` headers and revalidated with a conditional
+/// request, a `304 Not Modified` reusing it. No request is made while it is fresh according to `Cache-Control`.
+#[derive(Debug, Clone)]
+pub struct ManifestSource {
+    url: Url,
+    retry: RetryConfig,
+    signature: Option<ManifestSignatureConfig>,
+    pubkey: Option<String>,
+    cache: Arc<Mutex<Option<CachedManifest>>>,
+    store: Option<Store>,
+}
+
+impl ManifestSource {
+    pub fn new(url: Url) -> Self {
+        Self {
+            url,
+            retry: RetryConfig::default(),
+            signature: None,
+            pubkey: None,
+            cache: Default::default(),
+            store: None,
+        }
+    }
+
+    /// Verifies the release JSON against its minisign signature before reading it.
+    ///
+    /// Without a `pubkey` the signature cannot be checked, which fails the check if signatures are `required`.
+    pub fn signature(mut self, config: ManifestSignatureConfig, pubkey: Option<String>) -> Self {
+        self.signature = Some(config);
+        self.pubkey = pubkey;
+        self
+    }
+
+    /// Persists the cached release JSON in `store`, so it is reused across launches.
+    pub(crate) fn store(mut self, store: Store) -> Result<Self> {
+        *self.cache.lock().unwrap() = store.load()?.manifest;
+        self.store = Some(store);
+        Ok(self)
+    }
+
+    /// The cached release JSON of the endpoint, if any.
+    fn cached(&self) -> Option<CachedManifest> {
+        self.cache
+            .lock()
+            .unwrap()
+            .clone()
+            .filter(|cached| cached.url == self.url)
+    }
+
+    fn set_cached(&self, cached: Option<CachedManifest>) {
+        if let Some(store) = &self.store {
+            if let Err(e) = store.update(|data| data.manifest.clone_from(&cached)) {
+                tracing::warn!(error = %e, "failed to persist the release JSON cache");
+            }
+        }
+        *self.cache.lock().unwrap() = cached;
+    }
+
+    /// Requests the release JSON and its signature, revalidating the cached one if there is one.
+    async fn fetch(&self) -> Result<Option<(Vec<u8>, Option<String>)>> {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        let cached = self.cached();
+        if let Some(cached) = &cached {
+            if cached
+                .fresh_until
+                .is_some_and(|fresh_until| now < fresh_until)
+            {
+                tracing::debug!("release JSON is fresh, skipping the request");
+                return Ok(Some((
+                    cached.body.clone().into_bytes(),
+                    cached.signature.clone(),
+                )));
+            }
+        }
+
+        let mut headers = HeaderMap::new();
+        if let Some(cached) = &cached {
+            let validators = [
+                (IF_NONE_MATCH, &cached.etag),
+                (IF_MODIFIED_SINCE, &cached.last_modified),
+            ];
+            for (name, value) in validators {
+                if let Some(value) = value.as_deref().and_then(|v| HeaderValue::from_str(v).ok()) {
+                    headers.insert(name, value);
+                }
+            }
+        }
+
+        let client = ClientBuilder::new().build()?;
+        let response = send(&client, &self.url, headers, &self.retry).await?;
+        let fresh_until = fresh_until(response.headers(), now);
+        match (response.status(), cached) {
+            (StatusCode::NOT_MODIFIED, Some(mut cached)) => {
+                tracing::debug!("release JSON not modified");
+                let fetched = (cached.body.clone().into_bytes(), cached.signature.clone());
+                cached.fresh_until = fresh_until.flatten();
+                self.set_cached(Some(cached));
+                Ok(Some(fetched))
+            }
+            (StatusCode::NOT_MODIFIED, None) => Err(Error::CheckStatus {
+                url: self.url.clone(),
+                status: StatusCode::NOT_MODIFIED.as_u16(),
+                body: String::new(),
+            }),
+            (StatusCode::NO_CONTENT, _) => {
+                self.set_cached(None);
+                Ok(None)
+            }
+            _ => {
+                let etag = header_string(response.headers(), ETAG);
+                let last_modified = header_string(response.headers(), LAST_MODIFIED);
+                let signature = match &self.signature {
+                    Some(ManifestSignatureConfig {
+                        header: Some(header),
+                        ..
+                    }) => header_string(response.headers(), header.as_str()),
+                    Some(_) => self.detached_signature().await?,
+                    None => None,
+                };
+                let body = read_body(response, &self.url).await?;
+
+                // `no-store` responses and ones that can neither be revalidated nor reused are not cached
+                let cached = match (fresh_until, String::from_utf8(body.clone())) {
+                    (Some(fresh_until), Ok(text))
+                        if etag.is_some() || last_modified.is_some() || fresh_until.is_some() =>
+                    {
+                        Some(CachedManifest {
+                            url: self.url.clone(),
+                            etag,
+                            last_modified,
+                            fresh_until,
+                            body: text,
+                            signature: signature.clone(),
+                        })
+                    }
+                    _ => None,
+                };
+                self.set_cached(cached);
+                Ok(Some((body, signature)))
+            }
+        }
+    }
+
+    /// Reads the detached signature of the release JSON, `None` if there is none.
+    async fn detached_signature(&self) -> Result<Option<String>> {
+        let url = match self
+            .signature
+            .as_ref()
+            .and_then(|config| config.url.clone())
+        {
+            Some(url) => url,
+            None => {
+                let mut url = self.url.clone();
+                url.set_path(&format!("{}.sig", self.url.path()));
+                url
+            }
+        };
+
+        let body = if url.scheme() == "file" {
+            match tokio::fs::read(file_path(&url)?).await {
+                Ok(body) => Some(body),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+                Err(e) => return Err(e.into()),
+            }
+        } else {
+            let client = ClientBuilder::new().build()?;
+            match fetch_manifest(&client, &url, HeaderMap::new(), &self.retry).await {
+                Ok(body) => body,
+                Err(Error::CheckStatus { status: 404, .. }) => None,
+                Err(e) => return Err(e),
+            }
+        };
+        Ok(body.map(|body| String::from_utf8_lossy(&body).into_owned()))
+    }
+
+    /// Checks the release JSON against its signature, if signatures are configured.
+    fn verify(&self, body: &[u8], signature: Option<&str>) -> Result<()> {
+        let Some(config) = &self.signature else {
+            return Ok(());
+        };
+        let Some(signature) = signature else {
+            if config.required {
+                return Err(Error::UnsignedManifest {
+                    url: self.url.clone(),
+                });
+            }
+            tracing::warn!(url = %self.url, "the release JSON is not signed");
+            return Ok(());
+        };
+        let Some(pubkey) = &self.pubkey else {
+            if config.required {
+                return Err(Error::InvalidManifestSignature {
+                    url: self.url.clone(),
+                    message: "no `pubkey` is configured to verify it with".into(),
+                });
+            }
+            tracing::warn!("no `pubkey` is configured, the release JSON signature is not verified");
+            return Ok(());
+        };
+        verify_signature(body, signature, pubkey).map_err(|e| Error::InvalidManifestSignature {
+            url: self.url.clone(),
+            message: e.to_string(),
+        })
+    }
+
+    /// Sets the retry policy of the release JSON request.
+    pub fn retry(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+}
+
+#[async_trait]
+impl UpdateSource for ManifestSource {
+    async fn latest_release(
+        &self,
+        _target: &str,
+        _current_version: &Version,
+    ) -> Result<Option<RemoteRelease>> {
+        let (body, signature) = if self.url.scheme() == "file" {
+            let body = tokio::fs::read(file_path(&self.url)?).await?;
+            let signature = match self.signature {
+                Some(_) => self.detached_signature().await?,
+                None => None,
+            };
+            (body, signature)
+        } else {
+            match self.fetch().await? {
+                Some(fetched) => fetched,
+                None => return Ok(None),
+            }
+        };
+
+        if let Err(e) = self.verify(&body, signature.as_deref()) {
+            // do not reuse a release JSON that failed verification
+            self.set_cached(None);
+            return Err(e);
+        }
+        RemoteRelease::from_manifest(&body, &self.url).map(Some)
+    }
+}
+
+/// The source set on the builder, or else the one configured with `github`, `s3` or `endpoint`.
+pub(crate) fn from_config(
+    source: Option<Arc<dyn UpdateSource>>,
+    config: &Config,
+    store: &Store,
+) -> Result<Arc<dyn UpdateSource>> {
+    let is_endpoint = source.is_none()
+        && config.github.is_none()
+        && config.s3.is_none()
+        && config.endpoint.is_some();
+    if config.manifest_signature.is_some() && !is_endpoint {
+        return Err(Error::InvalidConfig(
+            "`manifestSignature` only applies to the release JSON of the `endpoint`".into(),
+        ));
+    }
+    let signature_required = config
+        .manifest_signature
+        .as_ref()
+        .is_some_and(|signature| signature.required);
+    if config.prevent_manifest_rollback && !(signature_required && config.pubkey.is_some()) {
+        return Err(Error::InvalidConfig(
+            "`preventManifestRollback` requires a `required` `manifestSignature` and a `pubkey`"
+                .into(),
+        ));
+    }
+
+    let retry = config.retry.clone();
+    Ok(
+        match (source, &config.github, &config.s3, &config.endpoint) {
+            (Some(source), ..) => source,
+            (None, Some(github), ..) => Arc::new(GithubSource::new(github.clone()).retry(retry)),
+            (None, None, Some(s3), _) => Arc::new(S3Source::new(s3.clone()).retry(retry)),
+            (None, None, None, Some(endpoint)) => {
+                let mut source = ManifestSource::new(endpoint.0.clone())
+                    .retry(retry)
+                    .store(store.clone())?;
+                if let Some(signature) = &config.manifest_signature {
+                    source = source.signature(signature.clone(), config.pubkey.clone());
+                }
+                Arc::new(source)
+            }
+            (None, None, None, None) => return Err(Error::EmptyEndpoints),
+        },
+    )
+}
+
+/// Requests release data from `url`, `None` meaning the server reported no update with a 204.
+pub(crate) async fn fetch_manifest(
+    client: &Client,
+    url: &Url,
+    headers: HeaderMap,
+    retry: &RetryConfig,
+) -> Result<Option<Vec<u8>>> {
+    let response = send(client, url, headers, retry).await?;
+    match response.status() {
+        StatusCode::NO_CONTENT => Ok(None),
+        // only expected in answer to a conditional request
+        StatusCode::NOT_MODIFIED => Err(Error::CheckStatus {
+            url: url.clone(),
+            status: StatusCode::NOT_MODIFIED.as_u16(),
+            body: String::new(),
+        }),
+        _ => read_body(response, url).await.map(Some),
+    }
+}
+
+/// Sends a GET request for release data, failing on error statuses.
+async fn send(
+    client: &Client,
+    url: &Url,
+    headers: HeaderMap,
+    retry: &RetryConfig,
+) -> Result<Response> {
+    let request = client.get(url.clone()).headers(headers);
+    let response = retry
+        .send(request)
+        .await
+        .map_err(|source| Error::CheckRequest {
+            url: url.clone(),
+            source,
+        })?;
+
+    let status = response.status();
+    if !status.is_success() && status != StatusCode::NOT_MODIFIED {
+        let body = response.text().await.unwrap_or_default();
+        return Err(Error::CheckStatus {
+            url: url.clone(),
+            status: status.as_u16(),
+            body: error::truncate_body(body),
+        });
+    }
+    Ok(response)
+}
+
+fn header_string(headers: &HeaderMap, name: impl AsHeaderName) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_string())
+}
+
+async fn read_body(response: Response, url: &Url) -> Result<Vec<u8>> {
+    let body = response
+        .bytes()
+        .await
+        .map_err(|source| Error::CheckRequest {
+            url: url.clone(),
+            source,
+        })?;
+    Ok(body.to_vec())
+}
+
+/// Unix timestamp until which a response is fresh according to its `Cache-Control` and `Age` headers.
+///
+/// Returns `None` if the response must not be stored, and `Some(None)` if it must be revalidated before reuse.
+fn fresh_until(headers: &HeaderMap, now: i64) -> Option<Option<i64>> {
+    let header = |name| {
+        headers
+            .get(name)
+            .and_then(|value: &HeaderValue| value.to_str().ok())
+    };
+    let directives = header(CACHE_CONTROL)
+        .map(|value| {
+            value
+                .split(',')
+                .map(|directive| directive.trim().to_ascii_lowercase())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    if directives.iter().any(|directive| directive == "no-store") {
+        return None;
+    }
+    if directives.iter().any(|directive| directive == "no-cache") {
+        return Some(None);
+    }
+    let max_age = directives.iter().find_map(|directive| {
+        directive
+            .strip_prefix("max-age=")
+            .and_then(|max_age| max_age.trim_matches('"').parse::<i64>().ok())
+    });
+    let age = header(AGE)
+        .and_then(|age| age.trim().parse::<i64>().ok())
+        .unwrap_or_default();
+    Some(max_age.map(|max_age| now + max_age - age))
+}
+
+/// Path of a `file://` URL.
+pub(crate) fn file_path(url: &Url) -> Result<PathBuf> {
+    url.to_file_path()
+        .map_err(|_| Error::InvalidFileLocation(url.to_string()))
+}
@@ -0,0 +1,194 @@
+use std::{
+    fs,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{Error, FailedInstall, JustUpdated, Result, UpdateResult};
+
+/// An installed update waiting for the new version to confirm it starts correctly.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PendingInstall {
+    /// Version that was running when the update was installed.
+    pub(crate) from_version: String,
+    /// Version that was installed.
+    pub(crate) version: String,
+    /// Path of the installed package.
+    pub(crate) path: PathBuf,
+    /// The previous package, restored on rollback.
+    pub(crate) backup: PathBuf,
+    /// Unix timestamp of the first launch of the new version.
+    pub(crate) launched_at: Option<i64>,
+}
+
+/// The last install attempt made by the updater.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct InstallRecord {
+    pub(crate) from_version: String,
+    pub(crate) version: String,
+    pub(crate) notes: Option<String>,
+    /// Set if the attempt failed.
+    pub(crate) error: Option<String>,
+}
+
+/// The last release JSON received from the endpoint, reused while it is fresh or not modified.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CachedManifest {
+    pub(crate) url: Url,
+    pub(crate) etag: Option<String>,
+    pub(crate) last_modified: Option<String>,
+    /// Unix timestamp until which the manifest is fresh, from `Cache-Control: max-age`.
+    pub(crate) fresh_until: Option<i64>,
+    pub(crate) body: String,
+    /// Signature of the body, from the configured header or the detached signature file.
+    #[serde(default)]
+    pub(crate) signature: Option<String>,
+}
+
+/// The newest release JSON seen, which older ones are rejected against.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LatestRelease {
+    pub(crate) version: String,
+    /// Unix timestamp of the `pub_date`.
+    pub(crate) pub_date: Option<i64>,
+}
+
+/// Data persisted by the updater.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct StoreData {
+    #[serde(default)]
+    pub(crate) pending: Option<PendingInstall>,
+    /// Versions that failed to start and must not be offered again.
+    #[serde(default)]
+    pub(crate) skipped_versions: Vec<String>,
+    #[serde(default)]
+    pub(crate) last_install: Option<InstallRecord>,
+    #[serde(default)]
+    pub(crate) manifest: Option<CachedManifest>,
+    #[serde(default)]
+    pub(crate) latest_release: Option<LatestRelease>,
+}
+
+/// Updater state persisted across launches in the app data directory.
+#[derive(Debug, Clone)]
+pub(crate) struct Store {
+    dir: PathBuf,
+    path: PathBuf,
+    /// Serializes the updates of the clones held by the plugin and its updates.
+    lock: Arc<Mutex<()>>,
+}
+
+impl Store {
+    pub(crate) fn new(dir: PathBuf) -> Self {
+        Self {
+            path: dir.join("universal-updater.json"),
+            dir,
+            lock: Default::default(),
+        }
+    }
+
+    /// Path a download is written to until it completes.
+    #[cfg(desktop)]
+    pub(crate) fn download_path(&self, file_name: &str) -> PathBuf {
+        self.dir.join("downloads").join(file_name)
+    }
+
+    /// Loads the persisted data, falling back to the defaults if there is none yet.
+    pub(crate) fn load(&self) -> Result<StoreData> {
+        match fs::read(&self.path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).map_err(|source| Error::InvalidStore {
+                path: self.path.clone(),
+                source,
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(StoreData::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Writes the data to a temporary file first, so a crash while saving keeps the previous state.
+    fn save(&self, data: &StoreData) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let mut file = tempfile::NamedTempFile::new_in(&self.dir)?;
+        serde_json::to_writer(&mut file, data)?;
+        file.as_file().sync_all()?;
+        file.persist(&self.path).map_err(|e| e.error)?;
+        Ok(())
+    }
+
+    /// Resolves the last install attempt against the running version and clears it,
+    /// so it is only reported on the first launch after it.
+    pub(crate) fn take_update_result(&self, current_version: &Version) -> Result<UpdateResult> {
+        let Some(record) = self.update(|data| data.last_install.take())? else {
+            return Ok(UpdateResult::default());
+        };
+
+        let result = match record.error {
+            Some(error) => UpdateResult {
+                failed: Some(FailedInstall {
+                    version: record.version,
+                    error,
+                }),
+                ..Default::default()
+            },
+            None if record.version == current_version.to_string() => UpdateResult {
+                just_updated: Some(JustUpdated {
+                    from: record.from_version,
+                    to: record.version,
+                    notes: record.notes,
+                }),
+                ..Default::default()
+            },
+            None => UpdateResult::default(),
+        };
+        Ok(result)
+    }
+
+    /// Modifies the persisted data, returning the result of `f`.
+    pub(crate) fn update<T, F: FnOnce(&mut StoreData) -> T>(&self, f: F) -> Result<T> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut data = self.load()?;
+        let value = f(&mut data);
+        self.save(&data)?;
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn corrupt_state_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("universal-updater.json");
+        fs::write(&path, b"{\"pending").unwrap();
+        let store = Store::new(dir.path().to_path_buf());
+
+        assert!(matches!(store.load(), Err(Error::InvalidStore { .. })));
+        assert!(store.update(|_| {}).is_err());
+        assert_eq!(fs::read(&path).unwrap(), b"{\"pending");
+    }
+
+    #[test]
+    fn update_replaces_the_state_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Store::new(dir.path().to_path_buf());
+
+        store
+            .update(|data| data.skipped_versions.push("1.1.0".into()))
+            .unwrap();
+
+        assert_eq!(store.load().unwrap().skipped_versions, ["1.1.0"]);
+        // the temporary file was renamed over the state file
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
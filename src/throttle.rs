@@ -0,0 +1,60 @@
+use std::time::{Duration, Instant};
+
+use tokio::sync::watch;
+
+/// How far a download may fall behind its rate limit before the credit is dropped,
+/// so a slow period is not followed by an unthrottled burst.
+const MAX_CREDIT: Duration = Duration::from_secs(1);
+
+/// Limits the rate of a download stream to a bytes per second value that can change at runtime.
+pub(crate) struct Throttle {
+    limit: watch::Receiver<Option<u64>>,
+    window_start: Instant,
+    window_bytes: u64,
+}
+
+impl Throttle {
+    pub(crate) fn new(limit: watch::Receiver<Option<u64>>) -> Self {
+        Self {
+            limit,
+            window_start: Instant::now(),
+            window_bytes: 0,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.window_start = Instant::now();
+        self.window_bytes = 0;
+    }
+
+    /// Accounts for `bytes` received and waits as long as needed to stay under the limit.
+    pub(crate) async fn consume(&mut self, bytes: usize) {
+        if self.limit.has_changed().unwrap_or(false) {
+            self.limit.borrow_and_update();
+            self.reset();
+        }
+
+        let limit = match *self.limit.borrow() {
+            Some(limit) if limit > 0 => limit,
+            _ => return,
+        };
+
+        self.window_bytes += bytes as u64;
+        let target = Duration::from_secs_f64(self.window_bytes as f64 / limit as f64);
+        let elapsed = self.window_start.elapsed();
+
+        match target.checked_sub(elapsed) {
+            Some(delay) => {
+                let changed = tokio::select! {
+                    _ = tokio::time::sleep(delay) => false,
+                    _ = self.limit.changed() => true,
+                };
+                if changed {
+                    self.reset();
+                }
+            }
+            None if elapsed - target > MAX_CREDIT => self.reset(),
+            None => {}
+        }
+    }
+}
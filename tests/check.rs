@@ -0,0 +1,366 @@
+mod common;
+
+use common::{app, json_target, manifest, run, try_app, updater, MockResponse, MockServer};
+use serde_json::json;
+use tauri_plugin_universal_updater::Error;
+
+#[test]
+fn check_finds_newer_release() {
+    let server = MockServer::start();
+    server.mock(
+        "/latest.json",
+        MockResponse::json(&manifest("1.1.0", "https://example.com/app-1.1.0.tar.gz")),
+    );
+    let app = app(json!({ "endpoint": server.url("/latest.json") }));
+
+    let update = run(updater(&app).check()).unwrap().unwrap();
+    assert_eq!(update.version(), "1.1.0");
+    assert_eq!(update.current_version(), "1.0.0");
+    assert_eq!(update.body(), Some("Bug fixes"));
+    assert_eq!(
+        update.date().map(|date| date.unix_timestamp()),
+        Some(1714564800)
+    );
+    assert_eq!(
+        update.download_url().as_str(),
+        "https://example.com/app-1.1.0.tar.gz"
+    );
+}
+
+#[test]
+fn check_accepts_name_and_prefixed_version() {
+    let server = MockServer::start();
+    server.mock(
+        "/latest.json",
+        MockResponse::json(&json!({
+            "name": "v2.0.0",
+            "platforms": {
+                json_target(): { "url": "https://example.com/app.tar.gz" }
+            }
+        })),
+    );
+    let app = app(json!({ "endpoint": server.url("/latest.json") }));
+
+    let update = run(updater(&app).check()).unwrap().unwrap();
+    assert_eq!(update.version(), "2.0.0");
+    assert_eq!(update.body(), None);
+    assert!(update.date().is_none());
+}
+
+#[test]
+fn check_uses_first_mirror_without_url() {
+    let server = MockServer::start();
+    server.mock(
+        "/latest.json",
+        MockResponse::json(&json!({
+            "version": "1.1.0",
+            "platforms": {
+                json_target(): {
+                    "urls": [
+                        "https://a.example.com/app.tar.gz",
+                        { "url": "https://b.example.com/app.tar.gz", "weight": 3 }
+                    ]
+                }
+            }
+        })),
+    );
+    let app = app(json!({ "endpoint": server.url("/latest.json") }));
+
+    let update = run(updater(&app).check()).unwrap().unwrap();
+    assert_eq!(
+        update.download_url().as_str(),
+        "https://a.example.com/app.tar.gz"
+    );
+    assert_eq!(update.mirrors().len(), 2);
+}
+
+#[test]
+fn check_resolves_relative_package_urls() {
+    let server = MockServer::start();
+    server.mock(
+        "/releases/latest.json",
+        MockResponse::json(&manifest("1.1.0", "1.1.0/app.tar.gz")),
+    );
+    let app = app(json!({ "endpoint": server.url("/releases/latest.json") }));
+
+    let update = run(updater(&app).check()).unwrap().unwrap();
+    assert_eq!(
+        update.download_url(),
+        &server.url("/releases/1.1.0/app.tar.gz")
+    );
+}
+
+#[test]
+fn check_reads_file_manifest() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("latest.json"),
+        serde_json::to_vec(&manifest("1.1.0", "app.tar.gz")).unwrap(),
+    )
+    .unwrap();
+    std::fs::write(dir.path().join("app.tar.gz"), b"package").unwrap();
+    let endpoint = url::Url::from_file_path(dir.path().join("latest.json")).unwrap();
+    let app = app(json!({ "endpoint": endpoint }));
+
+    let update = run(updater(&app).check()).unwrap().unwrap();
+    assert_eq!(update.version(), "1.1.0");
+    // relative package URLs resolve next to the release JSON, on disk as well
+    assert_eq!(
+        update.download_url(),
+        &url::Url::from_file_path(dir.path().join("app.tar.gz")).unwrap()
+    );
+    assert_eq!(
+        run(update.download(app.handle(), |_| {})).unwrap(),
+        b"package"
+    );
+}
+
+#[test]
+fn check_without_update_on_no_content() {
+    let server = MockServer::start();
+    server.mock("/latest.json", MockResponse::status(204));
+    let app = app(json!({ "endpoint": server.url("/latest.json") }));
+
+    assert!(run(updater(&app).check()).unwrap().is_none());
+}
+
+#[test]
+fn check_fails_on_not_found_without_retrying() {
+    let server = MockServer::start();
+    let app = app(json!({ "endpoint": server.url("/latest.json") }));
+
+    let Err(error) = run(updater(&app).check()) else {
+        panic!("the check succeeded");
+    };
+    assert!(
+        matches!(error, Error::CheckStatus { status: 404, .. }),
+        "{error:?}"
+    );
+    assert_eq!(error.code(), "checkStatus");
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[test]
+fn check_retries_server_errors() {
+    let server = MockServer::start();
+    server.mock("/latest.json", MockResponse::status(500));
+    let app = app(json!({
+        "endpoint": server.url("/latest.json"),
+        "retry": { "maxAttempts": 2, "baseDelay": 1, "jitter": 0 }
+    }));
+
+    let Err(error) = run(updater(&app).check()) else {
+        panic!("the check succeeded");
+    };
+    assert!(
+        matches!(error, Error::CheckStatus { status: 500, .. }),
+        "{error:?}"
+    );
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[test]
+fn check_fails_on_invalid_release() {
+    let server = MockServer::start();
+    server.mock(
+        "/latest.json",
+        MockResponse::json(&json!({ "version": "1.1.0" })),
+    );
+    let app = app(json!({ "endpoint": server.url("/latest.json") }));
+
+    let Err(error) = run(updater(&app).check()) else {
+        panic!("the check succeeded");
+    };
+    assert!(matches!(error, Error::InvalidRelease { .. }), "{error:?}");
+}
+
+#[test]
+fn check_compares_versions() {
+    let server = MockServer::start();
+    let app = app(json!({ "endpoint": server.url("/latest.json") }));
+
+    for (version, available) in [
+        ("0.9.0", false),
+        ("1.0.0", false),
+        ("1.0.0-beta.1", false),
+        ("1.0.1-beta.1", true),
+        ("1.0.1", true),
+        ("10.0.0", true),
+    ] {
+        server.mock(
+            "/latest.json",
+            MockResponse::json(&manifest(version, "https://example.com/app.tar.gz")),
+        );
+        let update = run(updater(&app).check()).unwrap();
+        assert_eq!(update.is_some(), available, "version {version}");
+    }
+}
+
+#[test]
+fn check_fails_on_missing_target() {
+    let server = MockServer::start();
+    server.mock(
+        "/latest.json",
+        MockResponse::json(&json!({
+            "version": "1.1.0",
+            "platforms": {
+                "plan9-mips": { "url": "https://example.com/app.tar.gz" }
+            }
+        })),
+    );
+    let app = app(json!({ "endpoint": server.url("/latest.json") }));
+
+    let Err(error) = run(updater(&app).check()) else {
+        panic!("the check succeeded");
+    };
+    assert!(
+        matches!(&error, Error::TargetNotFound(target) if *target == json_target()),
+        "{error:?}"
+    );
+}
+
+#[test]
+fn check_ignores_missing_target_without_update() {
+    let server = MockServer::start();
+    server.mock(
+        "/latest.json",
+        MockResponse::json(&json!({
+            "version": "0.9.0",
+            "platforms": {
+                "plan9-mips": { "url": "https://example.com/app.tar.gz" }
+            }
+        })),
+    );
+    let app = app(json!({ "endpoint": server.url("/latest.json") }));
+
+    assert!(run(updater(&app).check()).unwrap().is_none());
+}
+
+#[test]
+fn check_reuses_release_on_not_modified() {
+    let server = MockServer::start();
+    server.mock(
+        "/latest.json",
+        MockResponse::json(&manifest("1.1.0", "https://example.com/app.tar.gz"))
+            .header("etag", "\"v1\""),
+    );
+    let app = app(json!({ "endpoint": server.url("/latest.json") }));
+    assert!(run(updater(&app).check()).unwrap().is_some());
+
+    server.mock("/latest.json", MockResponse::status(304));
+    let update = run(updater(&app).check()).unwrap().unwrap();
+    assert_eq!(update.version(), "1.1.0");
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[test]
+fn check_skips_request_while_release_is_fresh() {
+    let server = MockServer::start();
+    server.mock(
+        "/latest.json",
+        MockResponse::json(&manifest("1.1.0", "https://example.com/app.tar.gz"))
+            .header("cache-control", "public, max-age=3600"),
+    );
+    let app = app(json!({ "endpoint": server.url("/latest.json") }));
+
+    for _ in 0..2 {
+        let update = run(updater(&app).check()).unwrap().unwrap();
+        assert_eq!(update.version(), "1.1.0");
+    }
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[test]
+fn check_rejects_expired_release() {
+    let server = MockServer::start();
+    let mut release = manifest("1.1.0", "https://example.com/app.tar.gz");
+    release["expires"] = json!("2024-06-01T12:00:00Z");
+    server.mock("/latest.json", MockResponse::json(&release));
+    let app = app(json!({ "endpoint": server.url("/latest.json") }));
+
+    let Err(error) = run(updater(&app).check()) else {
+        panic!("the check succeeded");
+    };
+    assert!(
+        matches!(&error, Error::StaleManifest { expired_at: Some(at) } if at == "2024-06-01T12:00:00Z"),
+        "{error:?}"
+    );
+}
+
+#[test]
+fn check_rejects_release_older_than_max_age() {
+    let server = MockServer::start();
+    server.mock(
+        "/latest.json",
+        MockResponse::json(&manifest("1.1.0", "https://example.com/app.tar.gz")),
+    );
+    let app = app(json!({
+        "endpoint": server.url("/latest.json"),
+        "manifestMaxAge": 86400
+    }));
+
+    let Err(error) = run(updater(&app).check()) else {
+        panic!("the check succeeded");
+    };
+    assert_eq!(error.code(), "staleManifest");
+}
+
+#[test]
+fn check_error_serializes_details() {
+    let server = MockServer::start();
+    server.mock("/latest.json", MockResponse::status(503));
+    let app = app(json!({
+        "endpoint": server.url("/latest.json"),
+        "retry": { "maxAttempts": 1 }
+    }));
+
+    let Err(error) = run(updater(&app).check()) else {
+        panic!("the check succeeded");
+    };
+    let error = serde_json::to_value(&error).unwrap();
+    assert_eq!(error["code"], "checkStatus");
+    assert_eq!(error["details"]["status"], 503);
+    assert_eq!(error["details"]["url"], server.url("/latest.json").as_str());
+    assert!(error["message"].as_str().unwrap().contains("503"));
+}
+
+#[test]
+fn check_reports_connection_errors() {
+    // a port nothing listens on
+    let address = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let app = app(json!({
+        "endpoint": format!("http://{address}/latest.json"),
+        "retry": { "maxAttempts": 1 }
+    }));
+
+    let Err(error) = run(updater(&app).check()) else {
+        panic!("the check succeeded");
+    };
+    assert!(matches!(error, Error::CheckRequest { .. }), "{error:?}");
+    assert_eq!(error.code(), "checkRequest");
+    let details = error.details().unwrap();
+    assert!(!details["cause"].as_str().unwrap().is_empty());
+}
+
+#[test]
+fn rollback_protection_requires_signed_manifest() {
+    let server = MockServer::start();
+    assert!(try_app(json!({
+        "endpoint": server.url("/latest.json"),
+        "preventManifestRollback": true
+    }))
+    .is_err());
+}
+
+#[test]
+fn manifest_signature_requires_endpoint_source() {
+    assert!(try_app(json!({
+        "github": { "owner": "owner", "repo": "repo" },
+        "pubkey": "RWQ=",
+        "manifestSignature": { "required": true }
+    }))
+    .is_err());
+}
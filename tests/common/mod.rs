@@ -0,0 +1,345 @@
+//! An in-process HTTP server and a mock app running the plugin, shared by the integration tests.
+
+#![allow(dead_code)]
+
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    future::Future,
+    net::SocketAddr,
+    ops::Deref,
+    sync::{Arc, Mutex},
+};
+
+use bytes::Bytes;
+use http_body_util::Full;
+use hyper::{
+    body::Incoming,
+    header::{ACCEPT_RANGES, CONTENT_RANGE, RANGE},
+    server::conn::http1,
+    service::service_fn,
+    Request, Response,
+};
+use hyper_util::rt::TokioIo;
+use serde_json::{json, Value};
+use tauri::{
+    test::{mock_builder, mock_context, noop_assets, MockRuntime},
+    App, Manager,
+};
+#[cfg(feature = "cli")]
+use tauri_plugin_universal_updater::minisign;
+use tauri_plugin_universal_updater::{Builder, UniversalUpdater};
+use tempfile::TempDir;
+use url::Url;
+
+/// Version of the app running the plugin.
+pub const CURRENT_VERSION: &str = "1.0.0";
+
+/// Runs a test on the Tauri async runtime, which the plugin and the server are spawned on.
+pub fn run<F: Future>(test: F) -> F::Output {
+    tauri::async_runtime::block_on(test)
+}
+
+/// A canned response of the [`MockServer`].
+#[derive(Clone)]
+pub struct MockResponse {
+    status: u16,
+    headers: Vec<(&'static str, String)>,
+    body: Vec<u8>,
+    ranges: bool,
+}
+
+impl MockResponse {
+    pub fn status(status: u16) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+            ranges: false,
+        }
+    }
+
+    pub fn json(value: &Value) -> Self {
+        Self::bytes(serde_json::to_vec(value).unwrap()).header("content-type", "application/json")
+    }
+
+    pub fn bytes(body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status: 200,
+            headers: Vec::new(),
+            body: body.into(),
+            ranges: false,
+        }
+    }
+
+    pub fn header(mut self, name: &'static str, value: impl Into<String>) -> Self {
+        self.headers.push((name, value.into()));
+        self
+    }
+
+    /// Answers `Range` requests with the requested part of the body, or `416` if it lies past the end.
+    pub fn ranges(mut self) -> Self {
+        self.ranges = true;
+        self
+    }
+}
+
+#[derive(Default)]
+struct ServerState {
+    /// Responses of every route, the first one is removed once answered unless it is the last.
+    routes: HashMap<String, Vec<MockResponse>>,
+    requests: Vec<String>,
+    ranges: Vec<String>,
+}
+
+/// An HTTP server on a random local port answering requests with canned responses by path, or by path and query.
+pub struct MockServer {
+    address: SocketAddr,
+    state: Arc<Mutex<ServerState>>,
+}
+
+impl MockServer {
+    pub fn start() -> Self {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.set_nonblocking(true).unwrap();
+        let address = listener.local_addr().unwrap();
+        let state = Arc::new(Mutex::new(ServerState::default()));
+
+        let server_state = state.clone();
+        tauri::async_runtime::spawn(async move {
+            let listener = tokio::net::TcpListener::from_std(listener).unwrap();
+            while let Ok((stream, _)) = listener.accept().await {
+                let state = server_state.clone();
+                tauri::async_runtime::spawn(async move {
+                    let service = service_fn(move |request: Request<Incoming>| {
+                        let response = respond(&state, &request);
+                        async move { Ok::<_, Infallible>(response) }
+                    });
+                    let _ = http1::Builder::new()
+                        .serve_connection(TokioIo::new(stream), service)
+                        .await;
+                });
+            }
+        });
+
+        Self { address, state }
+    }
+
+    pub fn url(&self, path: &str) -> Url {
+        Url::parse(&format!("http://{}{path}", self.address)).unwrap()
+    }
+
+    /// Answers requests to `path` with `response`.
+    ///
+    /// A `path` with a query only answers requests with exactly that query, taking precedence over the bare path.
+    pub fn mock(&self, path: &str, response: MockResponse) {
+        self.mock_sequence(path, vec![response]);
+    }
+
+    /// Answers requests to `path` with `responses` in turn, repeating the last one.
+    pub fn mock_sequence(&self, path: &str, responses: Vec<MockResponse>) {
+        assert!(!responses.is_empty());
+        self.state
+            .lock()
+            .unwrap()
+            .routes
+            .insert(path.to_string(), responses);
+    }
+
+    /// Paths of the requests received so far.
+    pub fn requests(&self) -> Vec<String> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// `Range` headers of the requests received so far.
+    pub fn ranges(&self) -> Vec<String> {
+        self.state.lock().unwrap().ranges.clone()
+    }
+}
+
+fn respond(state: &Mutex<ServerState>, request: &Request<Incoming>) -> Response<Full<Bytes>> {
+    let mut state = state.lock().unwrap();
+    let path = request.uri().path().to_string();
+    let path_and_query = request
+        .uri()
+        .path_and_query()
+        .map(|path_and_query| path_and_query.as_str())
+        .unwrap_or_default();
+    let route = if state.routes.contains_key(path_and_query) {
+        path_and_query.to_string()
+    } else {
+        path.clone()
+    };
+    let mock = match state.routes.get_mut(&route) {
+        Some(responses) if responses.len() > 1 => responses.remove(0),
+        Some(responses) => responses[0].clone(),
+        None => MockResponse::status(404),
+    };
+    state.requests.push(path);
+
+    let range = request
+        .headers()
+        .get(RANGE)
+        .and_then(|range| range.to_str().ok());
+    if let Some(range) = range {
+        state.ranges.push(range.to_string());
+    }
+
+    let mut response = Response::builder().status(mock.status);
+    for (name, value) in mock.headers {
+        response = response.header(name, value);
+    }
+    let mut body = mock.body;
+    if mock.ranges && mock.status == 200 {
+        response = response.header(ACCEPT_RANGES, "bytes");
+        match range.map(|range| parse_range(range, body.len())) {
+            Some(Some((start, end))) => {
+                response = response.status(206).header(
+                    CONTENT_RANGE,
+                    format!("bytes {start}-{}/{}", end - 1, body.len()),
+                );
+                body = body[start..end].to_vec();
+            }
+            Some(None) => {
+                response = response
+                    .status(416)
+                    .header(CONTENT_RANGE, format!("bytes */{}", body.len()));
+                body = Vec::new();
+            }
+            None => {}
+        }
+    }
+    response.body(Full::new(Bytes::from(body))).unwrap()
+}
+
+/// Parses a `bytes=<start>-[<end>]` range into the half-open range of the body it asks for.
+fn parse_range(range: &str, len: usize) -> Option<(usize, usize)> {
+    let (start, end) = range.strip_prefix("bytes=")?.split_once('-')?;
+    let start: usize = start.parse().ok()?;
+    let end = match end {
+        "" => len,
+        end => end.parse::<usize>().ok()?.saturating_add(1).min(len),
+    };
+    (start < end).then_some((start, end))
+}
+
+/// The `json_target` the plugin looks up on this machine.
+pub fn json_target() -> String {
+    let os = if cfg!(target_os = "linux") {
+        "linux"
+    } else if cfg!(target_os = "macos") {
+        "darwin"
+    } else {
+        "windows"
+    };
+    let arch = if cfg!(target_arch = "x86") {
+        "i686"
+    } else if cfg!(target_arch = "x86_64") {
+        "x86_64"
+    } else if cfg!(target_arch = "arm") {
+        "armv7"
+    } else {
+        "aarch64"
+    };
+    format!("{os}-{arch}")
+}
+
+/// A release JSON offering `version` for the current target, downloaded from `url`.
+pub fn manifest(version: &str, url: &str) -> Value {
+    json!({
+        "version": version,
+        "notes": "Bug fixes",
+        "pub_date": "2024-05-01T12:00:00Z",
+        "platforms": {
+            json_target(): { "url": url }
+        }
+    })
+}
+
+/// A minisign keypair, signing like `universal-updater-signer`.
+#[cfg(feature = "cli")]
+pub struct SigningKey {
+    secret: minisign::SecretKey,
+    public: minisign::PublicKey,
+}
+
+#[cfg(feature = "cli")]
+impl SigningKey {
+    pub fn new() -> Self {
+        let (secret, public) = minisign::generate().unwrap();
+        Self { secret, public }
+    }
+
+    /// The public key, as set in the `pubkey` configuration.
+    pub fn public_key(&self) -> String {
+        self.public.encode()
+    }
+
+    /// The signature of `data`, as written to `.sig` files.
+    pub fn sign(&self, data: &[u8]) -> String {
+        self.secret.sign(data, "latest.json")
+    }
+}
+
+/// A mock app with its own data directory, so stored state does not leak between tests.
+///
+/// The directory is removed when the app is dropped.
+pub struct TestApp {
+    app: App<MockRuntime>,
+    _data_dir: TempDir,
+}
+
+impl Deref for TestApp {
+    type Target = App<MockRuntime>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.app
+    }
+}
+
+/// Builds a mock app at [`CURRENT_VERSION`] running the plugin with `config`.
+pub fn app(config: Value) -> TestApp {
+    app_with(Builder::new(), config)
+}
+
+/// Like [`app`], returning the error if the plugin setup fails.
+pub fn try_app(config: Value) -> tauri::Result<TestApp> {
+    try_app_with(Builder::new(), config)
+}
+
+/// Like [`app`], running the plugin built by `builder`.
+pub fn app_with(builder: Builder, config: Value) -> TestApp {
+    try_app_with(builder, config).unwrap()
+}
+
+pub fn try_app_with(builder: Builder, config: Value) -> tauri::Result<TestApp> {
+    let data_dir = tempfile::tempdir().unwrap();
+    let app = try_launch(CURRENT_VERSION, builder.data_dir(data_dir.path()), config)?;
+    Ok(TestApp {
+        app,
+        _data_dir: data_dir,
+    })
+}
+
+/// Builds a mock app at `version` running the plugin built by `builder`, which sets the data directory.
+///
+/// Launches with the same data directory simulate restarts of the same app, e.g. into a newly installed version.
+pub fn launch(version: &str, builder: Builder, config: Value) -> App<MockRuntime> {
+    try_launch(version, builder, config).unwrap()
+}
+
+fn try_launch(version: &str, builder: Builder, config: Value) -> tauri::Result<App<MockRuntime>> {
+    let mut context = mock_context(noop_assets());
+    context.package_info_mut().version = version.parse().unwrap();
+    context
+        .config_mut()
+        .plugins
+        .0
+        .insert("universal-updater".into(), config);
+
+    mock_builder().plugin(builder.build()).build(context)
+}
+
+pub fn updater(app: &App<MockRuntime>) -> tauri::State<'_, UniversalUpdater<MockRuntime>> {
+    app.state::<UniversalUpdater<MockRuntime>>()
+}
@@ -0,0 +1,659 @@
+mod common;
+
+use common::{
+    app, app_with, json_target, launch, run, updater, MockResponse, MockServer, CURRENT_VERSION,
+};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use tauri_plugin_universal_updater::{Builder, DownloadEvent, Error};
+
+/// A package large enough to arrive in several chunks.
+fn package() -> Vec<u8> {
+    (0..256 * 1024).map(|i| (i % 251) as u8).collect()
+}
+
+fn serve_release(server: &MockServer, package: &[u8], sha256: &str) {
+    server.mock("/app.tar.gz", MockResponse::bytes(package));
+    server.mock(
+        "/latest.json",
+        MockResponse::json(&json!({
+            "version": "1.1.0",
+            "platforms": {
+                json_target(): {
+                    "url": server.url("/app.tar.gz"),
+                    "sha256": sha256
+                }
+            }
+        })),
+    );
+}
+
+#[test]
+fn download_reports_progress() {
+    let server = MockServer::start();
+    let package = package();
+    serve_release(&server, &package, &hex::encode(Sha256::digest(&package)));
+    let app = app(json!({ "endpoint": server.url("/latest.json") }));
+
+    let update = run(updater(&app).check()).unwrap().unwrap();
+    let mut events = Vec::new();
+    let bytes = run(update.download(app.handle(), |event| events.push(event))).unwrap();
+    assert_eq!(bytes, package);
+
+    let started = events
+        .iter()
+        .position(|event| matches!(event, DownloadEvent::Started { .. }))
+        .expect("no started event");
+    assert!(matches!(
+        events[started],
+        DownloadEvent::Started { content_length: Some(length) } if length == package.len() as u64
+    ));
+
+    let progress = events
+        .iter()
+        .filter_map(|event| match event {
+            DownloadEvent::Progress {
+                chunk_length,
+                downloaded,
+                total,
+                ..
+            } => Some((*chunk_length, *downloaded, *total)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert!(!progress.is_empty());
+    assert_eq!(
+        progress.iter().map(|(chunk, _, _)| chunk).sum::<usize>(),
+        package.len()
+    );
+    assert!(progress.windows(2).all(|pair| pair[0].1 < pair[1].1));
+    assert_eq!(
+        progress.last().map(|(_, downloaded, _)| *downloaded),
+        Some(package.len() as u64)
+    );
+    assert!(progress
+        .iter()
+        .all(|(_, _, total)| *total == Some(package.len() as u64)));
+
+    let finished = events
+        .iter()
+        .position(|event| matches!(event, DownloadEvent::Finished))
+        .expect("no finished event");
+    assert!(started < finished);
+    assert!(!events
+        .iter()
+        .any(|event| matches!(event, DownloadEvent::Failed { .. })));
+}
+
+#[test]
+fn download_rejects_checksum_mismatch() {
+    let server = MockServer::start();
+    let package = package();
+    serve_release(&server, &package, &hex::encode(Sha256::digest(b"other")));
+    let app = app(json!({ "endpoint": server.url("/latest.json") }));
+
+    let update = run(updater(&app).check()).unwrap().unwrap();
+    let mut events = Vec::new();
+    let error = run(update.download(app.handle(), |event| events.push(event))).unwrap_err();
+    assert!(matches!(error, Error::ChecksumMismatch { .. }), "{error:?}");
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, DownloadEvent::Failed { .. })));
+}
+
+#[test]
+fn download_fails_on_missing_package() {
+    let server = MockServer::start();
+    server.mock(
+        "/latest.json",
+        MockResponse::json(&common::manifest(
+            "1.1.0",
+            server.url("/missing.tar.gz").as_str(),
+        )),
+    );
+    let app = app(json!({
+        "endpoint": server.url("/latest.json"),
+        "retry": { "maxAttempts": 1 }
+    }));
+
+    let update = run(updater(&app).check()).unwrap().unwrap();
+    assert!(run(update.download(app.handle(), |_| {})).is_err());
+}
+
+#[test]
+fn failed_download_is_not_reported_on_next_launch() {
+    let server = MockServer::start();
+    server.mock(
+        "/latest.json",
+        MockResponse::json(&common::manifest(
+            "1.1.0",
+            server.url("/missing.tar.gz").as_str(),
+        )),
+    );
+    let config = json!({
+        "endpoint": server.url("/latest.json"),
+        "retry": { "maxAttempts": 1 }
+    });
+    let data_dir = tempfile::tempdir().unwrap();
+
+    let app = launch(
+        CURRENT_VERSION,
+        Builder::new().data_dir(data_dir.path()),
+        config.clone(),
+    );
+    let update = run(updater(&app).check()).unwrap().unwrap();
+    assert!(run(update.download(app.handle(), |_| {})).is_err());
+
+    // only install attempts are reported, the download never got that far
+    let app = launch(
+        CURRENT_VERSION,
+        Builder::new().data_dir(data_dir.path()),
+        config,
+    );
+    assert!(updater(&app).update_result().failed.is_none());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn install_replaces_appimage() {
+    let server = MockServer::start();
+    // the install only checks for an ELF header
+    let mut package = b"\x7fELF\x02\x01\x01".to_vec();
+    package.resize(4096, 0);
+    serve_release(&server, &package, &hex::encode(Sha256::digest(&package)));
+
+    let dir = tempfile::tempdir().unwrap();
+    let appimage = dir.path().join("app.AppImage");
+    std::fs::write(&appimage, b"\x7fELF old version").unwrap();
+    let app = app_with(
+        Builder::new().package_path(&appimage),
+        json!({ "endpoint": server.url("/latest.json") }),
+    );
+
+    let update = run(updater(&app).check()).unwrap().unwrap();
+    let bytes = run(update.download(app.handle(), |_| {})).unwrap();
+    update.install(bytes).unwrap();
+
+    assert_eq!(std::fs::read(&appimage).unwrap(), package);
+    // no rollback window is configured, so the previous version is not kept
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn install_extracts_appimage_from_bundle() {
+    let server = MockServer::start();
+    let mut appimage_bytes = b"\x7fELF\x02\x01\x01".to_vec();
+    appimage_bytes.resize(4096, 0);
+    let mut bundle = tar::Builder::new(flate2::write::GzEncoder::new(
+        Vec::new(),
+        flate2::Compression::default(),
+    ));
+    let mut header = tar::Header::new_gnu();
+    header.set_size(appimage_bytes.len() as u64);
+    header.set_mode(0o755);
+    bundle
+        .append_data(&mut header, "app_1.1.0_amd64.AppImage", &appimage_bytes[..])
+        .unwrap();
+    let bundle = bundle.into_inner().unwrap().finish().unwrap();
+    serve_release(&server, &bundle, &hex::encode(Sha256::digest(&bundle)));
+
+    let dir = tempfile::tempdir().unwrap();
+    let appimage = dir.path().join("app.AppImage");
+    std::fs::write(&appimage, b"\x7fELF old version").unwrap();
+    let app = app_with(
+        Builder::new().package_path(&appimage),
+        json!({ "endpoint": server.url("/latest.json") }),
+    );
+
+    let update = run(updater(&app).check()).unwrap().unwrap();
+    let mut events = Vec::new();
+    run(update.download_and_install(app.handle(), |event| events.push(event))).unwrap();
+
+    assert_eq!(std::fs::read(&appimage).unwrap(), appimage_bytes);
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, DownloadEvent::Extracting)));
+}
+
+#[test]
+fn install_from_file_requires_pubkey() {
+    let dir = tempfile::tempdir().unwrap();
+    let package = dir.path().join("app.tar.gz");
+    std::fs::write(&package, b"package").unwrap();
+    std::fs::write(dir.path().join("app.tar.gz.sig"), b"signature").unwrap();
+    let app = app(json!({ "endpoint": "https://example.com/latest.json" }));
+
+    let error = run(updater(&app).install_from_file(&package, "1.1.0".parse().unwrap(), |_| {}))
+        .unwrap_err();
+    assert!(matches!(error, Error::MissingPubkey), "{error:?}");
+}
+
+#[test]
+fn download_reports_rate_and_phases() {
+    let server = MockServer::start();
+    let package = package();
+    serve_release(&server, &package, &hex::encode(Sha256::digest(&package)));
+    let app = app(json!({ "endpoint": server.url("/latest.json") }));
+
+    let update = run(updater(&app).check()).unwrap().unwrap();
+    let mut events = Vec::new();
+    run(update.download(app.handle(), |event| events.push(event))).unwrap();
+
+    let position = |matches: fn(&DownloadEvent) -> bool| {
+        events
+            .iter()
+            .position(matches)
+            .expect("missing download phase")
+    };
+    let mirror = position(|event| matches!(event, DownloadEvent::Mirror { .. }));
+    let started = position(|event| matches!(event, DownloadEvent::Started { .. }));
+    let finished = position(|event| matches!(event, DownloadEvent::Finished));
+    let verifying = position(|event| matches!(event, DownloadEvent::Verifying));
+    assert!(mirror < started && started < finished && finished < verifying);
+
+    let Some(DownloadEvent::Progress {
+        bytes_per_second,
+        eta_seconds,
+        ..
+    }) = events
+        .iter()
+        .rev()
+        .find(|event| matches!(event, DownloadEvent::Progress { .. }))
+    else {
+        panic!("no progress event");
+    };
+    assert!(*bytes_per_second > 0);
+    assert_eq!(*eta_seconds, Some(0));
+}
+
+#[test]
+fn cancel_stops_running_download() {
+    let server = MockServer::start();
+    let package = package();
+    serve_release(&server, &package, &hex::encode(Sha256::digest(&package)));
+    let data_dir = tempfile::tempdir().unwrap();
+    let app = launch(
+        CURRENT_VERSION,
+        Builder::new().data_dir(data_dir.path()),
+        json!({ "endpoint": server.url("/latest.json") }),
+    );
+
+    let update = run(updater(&app).check()).unwrap().unwrap();
+    // slow enough that the download is still running after its first chunk
+    update.set_rate_limit(Some(64 * 1024));
+    let mut events = Vec::new();
+    let result = run(update.download(app.handle(), |event| {
+        if matches!(event, DownloadEvent::Progress { .. }) {
+            update.cancel();
+        }
+        events.push(event);
+    }));
+
+    assert!(matches!(result, Err(Error::Cancelled)), "{result:?}");
+    assert!(!events
+        .iter()
+        .any(|event| matches!(event, DownloadEvent::Finished)));
+    // the partial download is not kept without `resume`
+    assert!(!data_dir.path().join("downloads/1.1.0.part").exists());
+}
+
+#[test]
+fn cancelled_update_does_not_download() {
+    let server = MockServer::start();
+    let package = package();
+    serve_release(&server, &package, &hex::encode(Sha256::digest(&package)));
+    let app = app(json!({ "endpoint": server.url("/latest.json") }));
+
+    let update = run(updater(&app).check()).unwrap().unwrap();
+    update.cancel();
+    let result = run(update.download(app.handle(), |_| {}));
+    assert!(matches!(result, Err(Error::Cancelled)), "{result:?}");
+}
+
+#[test]
+fn paused_download_resumes_with_range_request() {
+    let server = MockServer::start();
+    let package = package();
+    serve_release(&server, &package, &hex::encode(Sha256::digest(&package)));
+    server.mock("/app.tar.gz", MockResponse::bytes(package.clone()).ranges());
+    let app = app(json!({ "endpoint": server.url("/latest.json") }));
+
+    let update = run(updater(&app).check()).unwrap().unwrap();
+    update.set_rate_limit(Some(64 * 1024));
+    let mut events = Vec::new();
+    let mut paused_once = false;
+    let bytes = std::thread::scope(|scope| {
+        scope.spawn(|| {
+            while !update.is_paused() {
+                std::thread::sleep(std::time::Duration::from_millis(10));
+            }
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            update.set_rate_limit(None);
+            update.resume();
+        });
+        run(update.download(app.handle(), |event| {
+            if matches!(event, DownloadEvent::Progress { .. }) && !paused_once {
+                paused_once = true;
+                update.pause();
+            }
+            events.push(event);
+        }))
+    })
+    .unwrap();
+    assert_eq!(bytes, package);
+
+    let paused = events
+        .iter()
+        .position(|event| matches!(event, DownloadEvent::Paused))
+        .expect("no paused event");
+    let resumed = events
+        .iter()
+        .position(|event| matches!(event, DownloadEvent::Resumed))
+        .expect("no resumed event");
+    assert!(paused < resumed);
+    // the download continued where it stopped instead of starting over
+    let ranges = server.ranges();
+    assert_eq!(ranges.len(), 1);
+    assert_ne!(ranges[0], "bytes=0-");
+    assert_eq!(
+        events
+            .iter()
+            .filter(|event| matches!(event, DownloadEvent::Started { .. }))
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn background_download_is_throttled() {
+    let server = MockServer::start();
+    let package = package();
+    serve_release(&server, &package, &hex::encode(Sha256::digest(&package)));
+    let limit = 512 * 1024;
+    let app = app(json!({
+        "endpoint": server.url("/latest.json"),
+        "backgroundDownloadLimit": limit
+    }));
+
+    let update = run(updater(&app).check()).unwrap().unwrap();
+    assert_eq!(update.rate_limit(), None);
+    update.set_background(true);
+    assert_eq!(update.rate_limit(), Some(limit));
+
+    let start = std::time::Instant::now();
+    let bytes = run(update.download(app.handle(), |_| {})).unwrap();
+    assert_eq!(bytes, package);
+    // half the limit takes about half a second
+    assert!(start.elapsed() >= std::time::Duration::from_millis(400));
+
+    update.set_background(false);
+    assert_eq!(update.rate_limit(), None);
+}
+
+#[test]
+fn download_retries_server_errors() {
+    let server = MockServer::start();
+    let package = package();
+    serve_release(&server, &package, &hex::encode(Sha256::digest(&package)));
+    server.mock_sequence(
+        "/app.tar.gz",
+        vec![
+            MockResponse::status(503),
+            MockResponse::bytes(package.clone()),
+        ],
+    );
+    let app = app(json!({
+        "endpoint": server.url("/latest.json"),
+        "retry": { "maxAttempts": 2, "baseDelay": 1, "jitter": 0 }
+    }));
+
+    let update = run(updater(&app).check()).unwrap().unwrap();
+    let bytes = run(update.download(app.handle(), |_| {})).unwrap();
+    assert_eq!(bytes, package);
+    assert_eq!(
+        server.requests(),
+        ["/latest.json", "/app.tar.gz", "/app.tar.gz"]
+    );
+}
+
+#[test]
+fn download_does_not_retry_client_errors() {
+    let server = MockServer::start();
+    server.mock(
+        "/latest.json",
+        MockResponse::json(&common::manifest(
+            "1.1.0",
+            server.url("/missing.tar.gz").as_str(),
+        )),
+    );
+    let app = app(json!({
+        "endpoint": server.url("/latest.json"),
+        "retry": { "maxAttempts": 3, "baseDelay": 1, "jitter": 0 }
+    }));
+
+    let update = run(updater(&app).check()).unwrap().unwrap();
+    assert!(run(update.download(app.handle(), |_| {})).is_err());
+    assert_eq!(server.requests(), ["/latest.json", "/missing.tar.gz"]);
+}
+
+#[test]
+fn download_fails_over_to_next_mirror() {
+    let server = MockServer::start();
+    let package = package();
+    server.mock("/mirror-b/app.tar.gz", MockResponse::bytes(package.clone()));
+    server.mock(
+        "/latest.json",
+        MockResponse::json(&json!({
+            "version": "1.1.0",
+            "platforms": {
+                json_target(): {
+                    "urls": [
+                        server.url("/mirror-a/app.tar.gz"),
+                        // a weight of 0 is only used once the other mirrors failed
+                        { "url": server.url("/mirror-b/app.tar.gz"), "weight": 0 }
+                    ],
+                    "sha256": hex::encode(Sha256::digest(&package))
+                }
+            }
+        })),
+    );
+    let app = app(json!({
+        "endpoint": server.url("/latest.json"),
+        "retry": { "maxAttempts": 1 }
+    }));
+
+    let update = run(updater(&app).check()).unwrap().unwrap();
+    let mut events = Vec::new();
+    let bytes = run(update.download(app.handle(), |event| events.push(event))).unwrap();
+    assert_eq!(bytes, package);
+
+    let mirrors = events
+        .iter()
+        .filter_map(|event| match event {
+            DownloadEvent::Mirror { url } => Some(url.path().to_string()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(mirrors, ["/mirror-a/app.tar.gz", "/mirror-b/app.tar.gz"]);
+}
+
+#[test]
+fn download_in_segments() {
+    let server = MockServer::start();
+    let package = package();
+    serve_release(&server, &package, &hex::encode(Sha256::digest(&package)));
+    server.mock("/app.tar.gz", MockResponse::bytes(package.clone()).ranges());
+    let app = app(json!({
+        "endpoint": server.url("/latest.json"),
+        "downloadSegments": 4,
+        "segmentMinSize": 1024
+    }));
+
+    let update = run(updater(&app).check()).unwrap().unwrap();
+    let bytes = run(update.download(app.handle(), |_| {})).unwrap();
+    assert_eq!(bytes, package);
+
+    let mut ranges = server.ranges();
+    ranges.sort();
+    let quarter = package.len() / 4;
+    let mut expected = (0..4)
+        .map(|i| format!("bytes={}-{}", i * quarter, (i + 1) * quarter - 1))
+        .collect::<Vec<_>>();
+    // the probe for the size and range support
+    expected.push("bytes=0-0".into());
+    expected.sort();
+    assert_eq!(ranges, expected);
+}
+
+#[test]
+fn download_without_range_support_uses_single_stream() {
+    let server = MockServer::start();
+    let package = package();
+    serve_release(&server, &package, &hex::encode(Sha256::digest(&package)));
+    let app = app(json!({
+        "endpoint": server.url("/latest.json"),
+        "downloadSegments": 4,
+        "segmentMinSize": 1024
+    }));
+
+    let update = run(updater(&app).check()).unwrap().unwrap();
+    let bytes = run(update.download(app.handle(), |_| {})).unwrap();
+    assert_eq!(bytes, package);
+    assert_eq!(server.ranges(), ["bytes=0-0"]);
+}
+
+#[test]
+fn download_resumes_partial_file() {
+    let server = MockServer::start();
+    let package = package();
+    serve_release(&server, &package, &hex::encode(Sha256::digest(&package)));
+    server.mock("/app.tar.gz", MockResponse::bytes(package.clone()).ranges());
+    let data_dir = tempfile::tempdir().unwrap();
+    // left behind by an earlier, interrupted download
+    std::fs::create_dir_all(data_dir.path().join("downloads")).unwrap();
+    std::fs::write(
+        data_dir.path().join("downloads/1.1.0.part"),
+        &package[..1000],
+    )
+    .unwrap();
+    let app = launch(
+        CURRENT_VERSION,
+        Builder::new().data_dir(data_dir.path()),
+        json!({ "endpoint": server.url("/latest.json"), "resume": true }),
+    );
+
+    let update = run(updater(&app).check()).unwrap().unwrap();
+    let bytes = run(update.download(app.handle(), |_| {})).unwrap();
+    assert_eq!(bytes, package);
+    assert_eq!(server.ranges(), ["bytes=1000-"]);
+}
+
+/// Serves the release of [`serve_release`] with a zstd patch from [`CURRENT_VERSION`] producing `patched`.
+fn serve_patched_release(server: &MockServer, package: &[u8], patch: &[u8], patched: &[u8]) {
+    server.mock("/app.tar.gz", MockResponse::bytes(package));
+    server.mock("/app.patch", MockResponse::bytes(patch));
+    server.mock(
+        "/latest.json",
+        MockResponse::json(&json!({
+            "version": "1.1.0",
+            "platforms": {
+                json_target(): {
+                    "url": server.url("/app.tar.gz"),
+                    "sha256": hex::encode(Sha256::digest(package)),
+                    "patches": [{
+                        "from": CURRENT_VERSION,
+                        "url": server.url("/app.patch"),
+                        "format": "zstd",
+                        "sha256": hex::encode(Sha256::digest(patched))
+                    }]
+                }
+            }
+        })),
+    );
+}
+
+/// The installed AppImage, its successor and a zstd patch between the two.
+fn appimages() -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+    use std::io::Write;
+
+    let old = b"\x7fELF version 1.0.0".repeat(1000);
+    let mut new = old.clone();
+    new.extend_from_slice(b"version 1.1.0");
+    let mut encoder = zstd::stream::write::Encoder::with_ref_prefix(Vec::new(), 3, &old).unwrap();
+    encoder.write_all(&new).unwrap();
+    let patch = encoder.finish().unwrap();
+    (old, new, patch)
+}
+
+#[test]
+fn download_applies_patch_to_installed_package() {
+    let server = MockServer::start();
+    let (old, new, patch) = appimages();
+    serve_patched_release(&server, &package(), &patch, &new);
+    let dir = tempfile::tempdir().unwrap();
+    let appimage = dir.path().join("app.AppImage");
+    std::fs::write(&appimage, &old).unwrap();
+    let app = app_with(
+        Builder::new().package_path(&appimage),
+        json!({ "endpoint": server.url("/latest.json") }),
+    );
+
+    let update = run(updater(&app).check()).unwrap().unwrap();
+    let bytes = run(update.download(app.handle(), |_| {})).unwrap();
+    assert_eq!(bytes, new);
+    assert!(!server.requests().iter().any(|path| path == "/app.tar.gz"));
+}
+
+#[test]
+fn download_falls_back_to_package_when_patch_fails() {
+    let server = MockServer::start();
+    let (old, new, _) = appimages();
+    let package = package();
+    serve_patched_release(&server, &package, b"not a zstd patch", &new);
+    let dir = tempfile::tempdir().unwrap();
+    let appimage = dir.path().join("app.AppImage");
+    std::fs::write(&appimage, &old).unwrap();
+    let app = app_with(
+        Builder::new().package_path(&appimage),
+        json!({ "endpoint": server.url("/latest.json") }),
+    );
+
+    let update = run(updater(&app).check()).unwrap().unwrap();
+    let bytes = run(update.download(app.handle(), |_| {})).unwrap();
+    assert_eq!(bytes, package);
+    let requests = server.requests();
+    assert!(requests.iter().any(|path| path == "/app.patch"));
+    assert!(requests.iter().any(|path| path == "/app.tar.gz"));
+}
+
+#[test]
+fn download_restarts_when_partial_file_is_complete() {
+    let server = MockServer::start();
+    let package = package();
+    serve_release(&server, &package, &hex::encode(Sha256::digest(&package)));
+    server.mock("/app.tar.gz", MockResponse::bytes(package.clone()).ranges());
+    let data_dir = tempfile::tempdir().unwrap();
+    // the earlier download finished but was never verified
+    std::fs::create_dir_all(data_dir.path().join("downloads")).unwrap();
+    std::fs::write(data_dir.path().join("downloads/1.1.0.part"), &package).unwrap();
+    let app = launch(
+        CURRENT_VERSION,
+        Builder::new().data_dir(data_dir.path()),
+        json!({ "endpoint": server.url("/latest.json"), "resume": true }),
+    );
+
+    let update = run(updater(&app).check()).unwrap().unwrap();
+    let bytes = run(update.download(app.handle(), |_| {})).unwrap();
+    assert_eq!(bytes, package);
+    // the server refuses the range past the end, the package is downloaded again from the start
+    assert_eq!(server.ranges(), [format!("bytes={}-", package.len())]);
+    assert_eq!(
+        server
+            .requests()
+            .iter()
+            .filter(|path| *path == "/app.tar.gz")
+            .count(),
+        2
+    );
+}
@@ -0,0 +1,163 @@
+mod common;
+
+use common::{app, json_target, run, updater, MockResponse, MockServer};
+use semver::Version;
+use serde_json::{json, Value};
+use tauri_plugin_universal_updater::{GithubConfig, GithubSource, UpdateSource};
+
+fn source(server: &MockServer, prerelease: bool) -> GithubSource {
+    GithubSource::new(GithubConfig {
+        owner: "owner".into(),
+        repo: "repo".into(),
+        api_url: server.url("/api/v3"),
+        token: Some("token".into()),
+        prerelease,
+    })
+}
+
+fn release(server: &MockServer, tag: &str, assets: &[&str]) -> Value {
+    let assets = assets
+        .iter()
+        .map(|name| {
+            json!({
+                "name": name,
+                "browser_download_url": server.url(&format!("/download/{tag}/{name}")),
+            })
+        })
+        .collect::<Vec<_>>();
+    json!({
+        "tag_name": tag,
+        "body": format!("notes of {tag}"),
+        "published_at": "2024-05-01T10:00:00Z",
+        "assets": assets,
+    })
+}
+
+#[test]
+fn latest_release_maps_assets_to_targets() {
+    let server = MockServer::start();
+    server.mock(
+        "/api/v3/repos/owner/repo/releases/latest",
+        MockResponse::json(&release(
+            &server,
+            "v1.2.0",
+            &[
+                "app_1.2.0_amd64.AppImage",
+                "app_1.2.0_amd64.AppImage.sig",
+                "app_1.2.0_amd64.deb",
+                "app_1.2.0_x64-setup.exe",
+                "app_1.2.0_x64_en-US.msi",
+                "app_1.2.0_x64_en-US.msi.sig",
+                "app_universal.app.tar.gz",
+            ],
+        )),
+    );
+    server.mock(
+        "/download/v1.2.0/app_1.2.0_amd64.AppImage.sig",
+        MockResponse::bytes("linux signature\n"),
+    );
+
+    let release =
+        run(source(&server, false).latest_release("linux-x86_64", &Version::new(1, 0, 0)))
+            .unwrap()
+            .unwrap();
+    assert_eq!(release.version, Version::new(1, 2, 0));
+    assert_eq!(release.notes.as_deref(), Some("notes of v1.2.0"));
+    assert!(release.pub_date.is_some());
+
+    let mut targets = release.platforms.keys().cloned().collect::<Vec<_>>();
+    targets.sort();
+    assert_eq!(
+        targets,
+        [
+            "darwin-aarch64",
+            "darwin-x86_64",
+            "linux-x86_64",
+            "windows-x86_64"
+        ]
+    );
+
+    let linux = &release.platforms["linux-x86_64"];
+    assert_eq!(
+        linux.url.path(),
+        "/download/v1.2.0/app_1.2.0_amd64.AppImage"
+    );
+    assert_eq!(linux.signature.as_deref(), Some("linux signature"));
+    // the NSIS installer is preferred over the MSI, and it has no `.sig` asset
+    let windows = &release.platforms["windows-x86_64"];
+    assert_eq!(
+        windows.url.path(),
+        "/download/v1.2.0/app_1.2.0_x64-setup.exe"
+    );
+    assert_eq!(windows.signature, None);
+
+    assert_eq!(
+        server.requests(),
+        [
+            "/api/v3/repos/owner/repo/releases/latest",
+            "/download/v1.2.0/app_1.2.0_amd64.AppImage.sig"
+        ]
+    );
+}
+
+#[test]
+fn latest_prerelease_skips_drafts() {
+    let server = MockServer::start();
+    let mut draft = release(&server, "v2.0.0", &["app_2.0.0_amd64.AppImage"]);
+    draft["draft"] = json!(true);
+    server.mock(
+        "/api/v3/repos/owner/repo/releases",
+        MockResponse::json(&json!([
+            release(&server, "v1.2.0", &["app_1.2.0_amd64.AppImage"]),
+            draft,
+            release(
+                &server,
+                "v1.3.0-beta.1",
+                &["app_1.3.0-beta.1_amd64.AppImage"]
+            ),
+            release(&server, "nightly", &["app_nightly_amd64.AppImage"]),
+        ])),
+    );
+
+    let release = run(source(&server, true).latest_release("linux-x86_64", &Version::new(1, 0, 0)))
+        .unwrap()
+        .unwrap();
+    assert_eq!(release.version, Version::parse("1.3.0-beta.1").unwrap());
+    assert_eq!(server.requests(), ["/api/v3/repos/owner/repo/releases"]);
+}
+
+#[test]
+fn latest_release_is_none_without_releases() {
+    let server = MockServer::start();
+    server.mock(
+        "/api/v3/repos/owner/repo/releases/latest",
+        MockResponse::status(404),
+    );
+
+    let release =
+        run(source(&server, false).latest_release("linux-x86_64", &Version::new(1, 0, 0))).unwrap();
+    assert!(release.is_none());
+}
+
+#[test]
+fn check_offers_github_release() {
+    let server = MockServer::start();
+    let name = match json_target().as_str() {
+        "linux-x86_64" => "app_1.1.0_amd64.AppImage",
+        "linux-aarch64" => "app_1.1.0_aarch64.AppImage",
+        "darwin-x86_64" | "darwin-aarch64" => "app_universal.app.tar.gz",
+        "windows-x86_64" => "app_1.1.0_x64-setup.exe",
+        "windows-aarch64" => "app_1.1.0_arm64-setup.exe",
+        target => panic!("no package name for `{target}`"),
+    };
+    server.mock(
+        "/api/v3/repos/owner/repo/releases/latest",
+        MockResponse::json(&release(&server, "v1.1.0", &[name])),
+    );
+    let app = app(json!({
+        "github": { "owner": "owner", "repo": "repo", "apiUrl": server.url("/api/v3") }
+    }));
+
+    let update = run(updater(&app).check()).unwrap().unwrap();
+    assert_eq!(update.version(), "1.1.0");
+}
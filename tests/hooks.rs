@@ -0,0 +1,149 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use common::{app_with, json_target, run, updater, MockResponse, MockServer};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use tauri_plugin_universal_updater::{Builder, Error, Update};
+
+type Calls = Arc<Mutex<Vec<String>>>;
+
+fn serve_release(server: &MockServer, package: &[u8], sha256: &str) {
+    server.mock("/app.tar.gz", MockResponse::bytes(package));
+    server.mock(
+        "/latest.json",
+        MockResponse::json(&json!({
+            "version": "1.1.0",
+            "platforms": {
+                json_target(): {
+                    "url": server.url("/app.tar.gz"),
+                    "sha256": sha256
+                }
+            }
+        })),
+    );
+}
+
+/// A builder with every hook recording its call, the install being vetoed with `veto` if set.
+fn builder(calls: &Calls, veto: Option<&'static str>) -> Builder {
+    let record = |name: &'static str| {
+        let calls = calls.clone();
+        move |update: &Update| {
+            calls
+                .lock()
+                .unwrap()
+                .push(format!("{name} {}", update.version()));
+        }
+    };
+    let before_install = record("before_install");
+    let failure_calls = calls.clone();
+    Builder::new()
+        .on_before_download(record("before_download"))
+        .on_after_verify(record("after_verify"))
+        .on_before_install(move |update| {
+            before_install(update);
+            veto.map_or(Ok(()), |reason| Err(reason.to_string()))
+        })
+        .on_after_install(record("after_install"))
+        .on_failure(move |update, error| {
+            failure_calls.lock().unwrap().push(format!(
+                "on_failure {} {}",
+                update.version(),
+                error.code()
+            ));
+        })
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn hooks_run_in_order() {
+    let server = MockServer::start();
+    // the install only checks for an ELF header
+    let mut package = b"\x7fELF\x02\x01\x01".to_vec();
+    package.resize(4096, 0);
+    serve_release(&server, &package, &hex::encode(Sha256::digest(&package)));
+    let dir = tempfile::tempdir().unwrap();
+    let appimage = dir.path().join("app.AppImage");
+    std::fs::write(&appimage, b"\x7fELF old version").unwrap();
+    let calls = Calls::default();
+    let app = app_with(
+        builder(&calls, None).package_path(&appimage),
+        json!({ "endpoint": server.url("/latest.json") }),
+    );
+
+    let update = run(updater(&app).check()).unwrap().unwrap();
+    run(update.download_and_install(app.handle(), |_| {})).unwrap();
+
+    assert_eq!(
+        *calls.lock().unwrap(),
+        [
+            "before_download 1.1.0",
+            "after_verify 1.1.0",
+            "before_install 1.1.0",
+            "after_install 1.1.0"
+        ]
+    );
+    assert_eq!(std::fs::read(&appimage).unwrap(), package);
+}
+
+#[test]
+fn before_install_hook_vetoes_install() {
+    let server = MockServer::start();
+    let package = b"package".to_vec();
+    serve_release(&server, &package, &hex::encode(Sha256::digest(&package)));
+    let dir = tempfile::tempdir().unwrap();
+    let appimage = dir.path().join("app.AppImage");
+    std::fs::write(&appimage, b"\x7fELF old version").unwrap();
+    let calls = Calls::default();
+    let app = app_with(
+        builder(&calls, Some("a meeting is running")).package_path(&appimage),
+        json!({ "endpoint": server.url("/latest.json") }),
+    );
+
+    let update = run(updater(&app).check()).unwrap().unwrap();
+    let Err(error) = run(update.download_and_install(app.handle(), |_| {})) else {
+        panic!("the install was not vetoed");
+    };
+
+    assert!(
+        matches!(&error, Error::InstallVetoed(reason) if reason == "a meeting is running"),
+        "{error:?}"
+    );
+    // the reason reaches JS in the serialized error
+    let error = serde_json::to_value(&error).unwrap();
+    assert_eq!(error["code"], "installVetoed");
+    assert_eq!(
+        error["message"],
+        "update install vetoed: a meeting is running"
+    );
+    assert_eq!(
+        *calls.lock().unwrap(),
+        [
+            "before_download 1.1.0",
+            "after_verify 1.1.0",
+            "before_install 1.1.0",
+            "on_failure 1.1.0 installVetoed"
+        ]
+    );
+    assert_eq!(std::fs::read(&appimage).unwrap(), b"\x7fELF old version");
+}
+
+#[test]
+fn failure_hook_runs_when_verification_fails() {
+    let server = MockServer::start();
+    serve_release(&server, b"package", &hex::encode(Sha256::digest(b"other")));
+    let calls = Calls::default();
+    let app = app_with(
+        builder(&calls, None),
+        json!({ "endpoint": server.url("/latest.json") }),
+    );
+
+    let update = run(updater(&app).check()).unwrap().unwrap();
+    assert!(run(update.download_and_install(app.handle(), |_| {})).is_err());
+
+    assert_eq!(
+        *calls.lock().unwrap(),
+        ["before_download 1.1.0", "on_failure 1.1.0 checksumMismatch"]
+    );
+}
@@ -0,0 +1,113 @@
+#![cfg(feature = "cli")]
+
+use std::{
+    fs,
+    path::Path,
+    process::{Command, Output},
+};
+
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+fn manifest(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_universal-updater-manifest"))
+        .current_dir(dir)
+        .args([
+            "--version",
+            "1.2.0",
+            "--base-url",
+            "https://releases.example.com/1.2.0",
+        ])
+        .args(args)
+        .output()
+        .expect("failed to run the manifest tool")
+}
+
+fn bundle(dir: &Path) {
+    let bundle = dir.join("bundle");
+    fs::create_dir(&bundle).unwrap();
+    fs::write(bundle.join("app_1.2.0_amd64.AppImage.tar.gz"), b"linux").unwrap();
+    fs::write(
+        bundle.join("app_1.2.0_amd64.AppImage.tar.gz.sig"),
+        "signature\n",
+    )
+    .unwrap();
+    fs::write(bundle.join("MyApp.app.tar.gz"), b"macos").unwrap();
+}
+
+#[test]
+fn manifest_from_bundle() {
+    let dir = tempfile::tempdir().unwrap();
+    bundle(dir.path());
+    fs::write(dir.path().join("NOTES.md"), "Bug fixes").unwrap();
+
+    let output = manifest(
+        dir.path(),
+        &[
+            "--notes",
+            "NOTES.md",
+            "--pub-date",
+            "2024-05-01T12:00:00Z",
+            "--expires",
+            "2024-06-01T12:00:00Z",
+            "--target",
+            "darwin-aarch64",
+            "--output",
+            "latest.json",
+            "bundle",
+        ],
+    );
+    assert!(output.status.success(), "{output:?}");
+    // the macOS package has no `.sig` file
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("`https://releases.example.com/1.2.0/MyApp.app.tar.gz` has no `.sig` file"));
+
+    let manifest: Value =
+        serde_json::from_str(&fs::read_to_string(dir.path().join("latest.json")).unwrap()).unwrap();
+    assert_eq!(manifest["version"], "1.2.0");
+    assert_eq!(manifest["notes"], "Bug fixes");
+    assert_eq!(manifest["pub_date"], "2024-05-01T12:00:00Z");
+    assert_eq!(manifest["expires"], "2024-06-01T12:00:00Z");
+
+    let linux = &manifest["platforms"]["linux-x86_64"];
+    assert_eq!(
+        linux["url"],
+        "https://releases.example.com/1.2.0/app_1.2.0_amd64.AppImage.tar.gz"
+    );
+    assert_eq!(linux["signature"], "signature");
+    assert_eq!(linux["sha256"], hex::encode(Sha256::digest(b"linux")));
+    let macos = &manifest["platforms"]["darwin-aarch64"];
+    assert_eq!(
+        macos["url"],
+        "https://releases.example.com/1.2.0/MyApp.app.tar.gz"
+    );
+    assert!(macos["signature"].is_null());
+}
+
+#[test]
+fn package_without_arch_needs_target() {
+    let dir = tempfile::tempdir().unwrap();
+    bundle(dir.path());
+
+    let output = manifest(dir.path(), &["bundle"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("pass a `darwin-<arch>` target"));
+
+    let output = manifest(dir.path(), &["--target", "darwin", "bundle"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn empty_bundle_fails() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("bundle")).unwrap();
+
+    let output = manifest(dir.path(), &["bundle"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("no updater packages found"));
+}
@@ -0,0 +1,141 @@
+#![cfg(target_os = "linux")]
+
+mod common;
+
+use std::{fs, path::Path, thread, time::Duration};
+
+use common::{json_target, launch, run, updater, MockResponse, MockServer, CURRENT_VERSION};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use tauri_plugin_universal_updater::{Builder, Error};
+use tempfile::TempDir;
+
+/// An installed AppImage at [`CURRENT_VERSION`] and a server offering 1.1.0 with a rollback window of `window`.
+struct Setup {
+    _server: MockServer,
+    dir: TempDir,
+    data_dir: TempDir,
+    package: Vec<u8>,
+    config: Value,
+}
+
+impl Setup {
+    fn new(window: u64) -> Self {
+        let server = MockServer::start();
+        // the install only checks for an ELF header
+        let mut package = b"\x7fELF\x02\x01\x01".to_vec();
+        package.resize(4096, 1);
+        server.mock("/app.tar.gz", MockResponse::bytes(package.clone()));
+        server.mock(
+            "/latest.json",
+            MockResponse::json(&json!({
+                "version": "1.1.0",
+                "platforms": {
+                    json_target(): {
+                        "url": server.url("/app.tar.gz"),
+                        "sha256": hex::encode(Sha256::digest(&package))
+                    }
+                }
+            })),
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("app.AppImage"), b"\x7fELF old version").unwrap();
+        let config = json!({
+            "endpoint": server.url("/latest.json"),
+            "rollbackWindow": window
+        });
+        Self {
+            _server: server,
+            dir,
+            data_dir: tempfile::tempdir().unwrap(),
+            package,
+            config,
+        }
+    }
+
+    fn appimage(&self) -> std::path::PathBuf {
+        self.dir.path().join("app.AppImage")
+    }
+
+    /// Launches the app as `version`, as after restarting into the installed AppImage.
+    fn launch(&self, version: &str) -> tauri::App<tauri::test::MockRuntime> {
+        launch(
+            version,
+            Builder::new()
+                .data_dir(self.data_dir.path())
+                .package_path(self.appimage()),
+            self.config.clone(),
+        )
+    }
+
+    fn install_update(&self) {
+        let app = self.launch(CURRENT_VERSION);
+        let update = run(updater(&app).check()).unwrap().unwrap();
+        let bytes = run(update.download(app.handle(), |_| {})).unwrap();
+        update.install(bytes).unwrap();
+
+        assert_eq!(fs::read(self.appimage()).unwrap(), self.package);
+        assert!(backup(&self.appimage()).exists());
+    }
+}
+
+fn backup(appimage: &Path) -> std::path::PathBuf {
+    appimage.with_file_name("app.AppImage.bak")
+}
+
+#[test]
+fn unconfirmed_update_is_rolled_back_and_reported_after_restart() {
+    let setup = Setup::new(60);
+    setup.install_update();
+
+    let app = setup.launch("1.1.0");
+    assert!(updater(&app).just_updated().is_some());
+    drop(app);
+
+    // the first launch never confirmed, so the next one restores the previous version
+    let app = setup.launch("1.1.0");
+    assert_eq!(fs::read(setup.appimage()).unwrap(), b"\x7fELF old version");
+    assert!(updater(&app).update_result().failed.is_none());
+    drop(app);
+
+    // the restored version reports the rollback and no longer offers the failed version
+    let app = setup.launch(CURRENT_VERSION);
+    let updater = updater(&app);
+    let failed = updater.update_result().failed.as_ref().unwrap();
+    assert_eq!(failed.version, "1.1.0");
+    assert!(run(updater.check()).unwrap().is_none());
+}
+
+#[test]
+fn update_confirmed_within_window_is_kept() {
+    let setup = Setup::new(60);
+    setup.install_update();
+
+    let app = setup.launch("1.1.0");
+    updater(&app).mark_update_successful().unwrap();
+    assert!(!backup(&setup.appimage()).exists());
+    drop(app);
+
+    let app = setup.launch("1.1.0");
+    assert_eq!(fs::read(setup.appimage()).unwrap(), setup.package);
+    assert!(updater(&app).update_result().failed.is_none());
+}
+
+#[test]
+fn update_confirmed_after_window_is_rolled_back() {
+    let setup = Setup::new(0);
+    setup.install_update();
+
+    let app = setup.launch("1.1.0");
+    thread::sleep(Duration::from_millis(1100));
+    let result = updater(&app).mark_update_successful();
+    assert!(
+        matches!(&result, Err(Error::RollbackWindowExpired { version }) if version == "1.1.0"),
+        "{result:?}"
+    );
+    drop(app);
+
+    setup.launch("1.1.0");
+    assert_eq!(fs::read(setup.appimage()).unwrap(), b"\x7fELF old version");
+}
@@ -0,0 +1,147 @@
+mod common;
+
+use common::{run, MockResponse, MockServer};
+use semver::Version;
+use tauri_plugin_universal_updater::{S3Config, S3Source, UpdateSource};
+
+fn source(server: &MockServer) -> S3Source {
+    S3Source::new(S3Config {
+        endpoint: server.url("/"),
+        bucket: "bucket".into(),
+        prefix: "releases/".into(),
+        region: "us-east-1".into(),
+        path_style: true,
+        access_key_id: None,
+        secret_access_key: None,
+        session_token: None,
+    })
+}
+
+fn listing(body: &str) -> MockResponse {
+    MockResponse::bytes(format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?><ListBucketResult>{body}</ListBucketResult>"#
+    ))
+    .header("content-type", "application/xml")
+}
+
+#[test]
+fn latest_release_follows_truncated_listings() {
+    let server = MockServer::start();
+    server.mock(
+        "/bucket?delimiter=%2F&list-type=2&prefix=releases%2F",
+        listing(
+            "<IsTruncated>true</IsTruncated><NextContinuationToken>page2</NextContinuationToken>\
+             <CommonPrefixes><Prefix>releases/1.0.0/</Prefix></CommonPrefixes>",
+        ),
+    );
+    server.mock(
+        "/bucket?continuation-token=page2&delimiter=%2F&list-type=2&prefix=releases%2F",
+        listing(
+            "<IsTruncated>false</IsTruncated>\
+             <CommonPrefixes><Prefix>releases/1.2.0/</Prefix></CommonPrefixes>\
+             <CommonPrefixes><Prefix>releases/1.1.0/</Prefix></CommonPrefixes>",
+        ),
+    );
+    server.mock(
+        "/bucket?list-type=2&prefix=releases%2F1.2.0%2F",
+        listing(
+            "<IsTruncated>false</IsTruncated>\
+             <Contents><Key>releases/1.2.0/app_1.2.0_amd64.AppImage</Key>\
+             <LastModified>2024-05-01T10:00:00.000Z</LastModified></Contents>\
+             <Contents><Key>releases/1.2.0/app_1.2.0_amd64.AppImage.sig</Key></Contents>\
+             <Contents><Key>releases/1.2.0/app_1.2.0_x64-setup.exe</Key></Contents>\
+             <Contents><Key>releases/1.2.0/notes.txt</Key></Contents>",
+        ),
+    );
+    server.mock(
+        "/bucket/releases/1.2.0/app_1.2.0_amd64.AppImage.sig",
+        MockResponse::bytes("linux signature\n"),
+    );
+
+    let release = run(source(&server).latest_release("linux-x86_64", &Version::new(1, 0, 0)))
+        .unwrap()
+        .unwrap();
+    assert_eq!(release.version, Version::new(1, 2, 0));
+    assert!(release.pub_date.is_some());
+
+    let mut targets = release.platforms.keys().cloned().collect::<Vec<_>>();
+    targets.sort();
+    assert_eq!(targets, ["linux-x86_64", "windows-x86_64"]);
+
+    let linux = &release.platforms["linux-x86_64"];
+    assert_eq!(
+        linux.url.path(),
+        "/bucket/releases/1.2.0/app_1.2.0_amd64.AppImage"
+    );
+    assert_eq!(linux.signature.as_deref(), Some("linux signature"));
+    // no `.sig` object is listed for the Windows package, so none is requested
+    assert_eq!(release.platforms["windows-x86_64"].signature, None);
+
+    let requests = server.requests();
+    assert_eq!(requests.iter().filter(|path| *path == "/bucket").count(), 3);
+    assert_eq!(
+        requests
+            .iter()
+            .filter(|path| path.ends_with(".sig"))
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn latest_release_is_none_without_release_folders() {
+    let server = MockServer::start();
+    server.mock(
+        "/bucket?delimiter=%2F&list-type=2&prefix=releases%2F",
+        listing(
+            "<IsTruncated>false</IsTruncated>\
+             <CommonPrefixes><Prefix>releases/latest/</Prefix></CommonPrefixes>",
+        ),
+    );
+
+    let release =
+        run(source(&server).latest_release("linux-x86_64", &Version::new(1, 0, 0))).unwrap();
+    assert!(release.is_none());
+}
+
+#[test]
+fn latest_release_skips_releases_without_the_target() {
+    let server = MockServer::start();
+    server.mock(
+        "/bucket?delimiter=%2F&list-type=2&prefix=releases%2F",
+        listing(
+            "<IsTruncated>false</IsTruncated>\
+             <CommonPrefixes><Prefix>releases/1.0.0/</Prefix></CommonPrefixes>\
+             <CommonPrefixes><Prefix>releases/1.2.0/</Prefix></CommonPrefixes>\
+             <CommonPrefixes><Prefix>releases/1.1.0/</Prefix></CommonPrefixes>",
+        ),
+    );
+    // 1.2.0 is only built for Windows so far
+    server.mock(
+        "/bucket?list-type=2&prefix=releases%2F1.2.0%2F",
+        listing(
+            "<IsTruncated>false</IsTruncated>\
+             <Contents><Key>releases/1.2.0/app_1.2.0_x64-setup.exe</Key></Contents>",
+        ),
+    );
+    server.mock(
+        "/bucket?list-type=2&prefix=releases%2F1.1.0%2F",
+        listing(
+            "<IsTruncated>false</IsTruncated>\
+             <Contents><Key>releases/1.1.0/app_1.1.0_amd64.AppImage</Key></Contents>",
+        ),
+    );
+
+    let release = run(source(&server).latest_release("linux-x86_64", &Version::new(1, 0, 0)))
+        .unwrap()
+        .unwrap();
+    assert_eq!(release.version, Version::new(1, 1, 0));
+    assert!(release.platforms.contains_key("linux-x86_64"));
+
+    let release =
+        run(source(&server).latest_release("linux-x86_64", &Version::new(1, 1, 0))).unwrap();
+    assert!(release.is_none());
+    // the bucket and the folders of 1.2.0 and 1.1.0, then the bucket and 1.2.0 again:
+    // the running version and older ones are never listed
+    assert_eq!(server.requests().len(), 5);
+}
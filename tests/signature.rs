@@ -0,0 +1,215 @@
+//! Signing requires the minisign module of the `cli` feature.
+
+#![cfg(feature = "cli")]
+
+mod common;
+
+use std::path::{Path, PathBuf};
+
+use common::{app, app_with, manifest, run, updater, MockResponse, MockServer, SigningKey};
+use serde_json::json;
+use tauri_plugin_universal_updater::{Builder, Error};
+
+fn release() -> Vec<u8> {
+    serde_json::to_vec(&manifest("1.1.0", "https://example.com/app.tar.gz")).unwrap()
+}
+
+#[test]
+fn check_verifies_signature_header() {
+    let key = SigningKey::new();
+    let server = MockServer::start();
+    server.mock(
+        "/latest.json",
+        MockResponse::bytes(release()).header("x-manifest-signature", key.sign(&release())),
+    );
+    let app = app(json!({
+        "endpoint": server.url("/latest.json"),
+        "pubkey": key.public_key(),
+        "manifestSignature": { "required": true, "header": "X-Manifest-Signature" }
+    }));
+
+    let update = run(updater(&app).check()).unwrap().unwrap();
+    assert_eq!(update.version(), "1.1.0");
+    assert_eq!(server.requests(), ["/latest.json"]);
+}
+
+#[test]
+fn check_verifies_detached_signature() {
+    let key = SigningKey::new();
+    let server = MockServer::start();
+    server.mock("/latest.json", MockResponse::bytes(release()));
+    server.mock(
+        "/latest.json.sig",
+        MockResponse::bytes(key.sign(&release())),
+    );
+    let app = app(json!({
+        "endpoint": server.url("/latest.json"),
+        "pubkey": key.public_key(),
+        "manifestSignature": { "required": true }
+    }));
+
+    assert!(run(updater(&app).check()).unwrap().is_some());
+}
+
+#[test]
+fn check_rejects_tampered_release() {
+    let key = SigningKey::new();
+    let server = MockServer::start();
+    let tampered =
+        serde_json::to_vec(&manifest("0.1.0", "https://example.com/app.tar.gz")).unwrap();
+    server.mock("/latest.json", MockResponse::bytes(tampered));
+    server.mock(
+        "/latest.json.sig",
+        MockResponse::bytes(key.sign(&release())),
+    );
+    let app = app(json!({
+        "endpoint": server.url("/latest.json"),
+        "pubkey": key.public_key(),
+        "manifestSignature": {}
+    }));
+
+    let Err(error) = run(updater(&app).check()) else {
+        panic!("the check succeeded");
+    };
+    assert!(
+        matches!(error, Error::InvalidManifestSignature { .. }),
+        "{error:?}"
+    );
+    assert_eq!(error.code(), "invalidManifestSignature");
+}
+
+#[test]
+fn check_rejects_unsigned_release_if_required() {
+    let key = SigningKey::new();
+    let server = MockServer::start();
+    server.mock("/latest.json", MockResponse::bytes(release()));
+    let app = app(json!({
+        "endpoint": server.url("/latest.json"),
+        "pubkey": key.public_key(),
+        "manifestSignature": { "required": true }
+    }));
+
+    let Err(error) = run(updater(&app).check()) else {
+        panic!("the check succeeded");
+    };
+    assert!(matches!(error, Error::UnsignedManifest { .. }), "{error:?}");
+}
+
+#[test]
+fn check_accepts_unsigned_release_if_optional() {
+    let key = SigningKey::new();
+    let server = MockServer::start();
+    server.mock("/latest.json", MockResponse::bytes(release()));
+    let app = app(json!({
+        "endpoint": server.url("/latest.json"),
+        "pubkey": key.public_key(),
+        "manifestSignature": { "required": false }
+    }));
+
+    assert!(run(updater(&app).check()).unwrap().is_some());
+    assert_eq!(server.requests(), ["/latest.json", "/latest.json.sig"]);
+}
+
+#[test]
+fn check_rejects_rolled_back_release() {
+    let key = SigningKey::new();
+    let server = MockServer::start();
+    let app = app(json!({
+        "endpoint": server.url("/latest.json"),
+        "pubkey": key.public_key(),
+        "manifestSignature": { "required": true, "header": "X-Manifest-Signature" },
+        "preventManifestRollback": true
+    }));
+    let serve = |version: &str| {
+        let release =
+            serde_json::to_vec(&manifest(version, "https://example.com/app.tar.gz")).unwrap();
+        let signature = key.sign(&release);
+        server.mock(
+            "/latest.json",
+            MockResponse::bytes(release).header("x-manifest-signature", signature),
+        );
+    };
+
+    serve("1.2.0");
+    assert!(run(updater(&app).check()).unwrap().is_some());
+
+    serve("1.1.0");
+    let Err(error) = run(updater(&app).check()) else {
+        panic!("the check succeeded");
+    };
+    assert!(
+        matches!(&error, Error::ManifestRollback { version, latest } if version == "1.1.0" && latest == "1.2.0"),
+        "{error:?}"
+    );
+}
+
+/// A package for `install_from_file`, the install only checks for an ELF header.
+fn package() -> Vec<u8> {
+    let mut package = b"\x7fELF\x02\x01\x01".to_vec();
+    package.resize(4096, 0);
+    package
+}
+
+/// Writes [`package`] to `dir`, with a `.sig` file if `signature` is set.
+fn package_file(dir: &Path, signature: Option<&str>) -> PathBuf {
+    let path = dir.join("app_1.1.0_amd64.AppImage");
+    std::fs::write(&path, package()).unwrap();
+    if let Some(signature) = signature {
+        std::fs::write(dir.join("app_1.1.0_amd64.AppImage.sig"), signature).unwrap();
+    }
+    path
+}
+
+/// Installs the package at `path` over an AppImage in `dir`, returning the result and the AppImage afterwards.
+fn install_from_file(dir: &Path, key: &SigningKey, path: &Path) -> (Result<(), Error>, Vec<u8>) {
+    let appimage = dir.join("app.AppImage");
+    std::fs::write(&appimage, b"\x7fELF old version").unwrap();
+    let app = app_with(
+        Builder::new().package_path(&appimage),
+        json!({
+            "endpoint": "https://example.com/latest.json",
+            "pubkey": key.public_key()
+        }),
+    );
+
+    let result = run(updater(&app).install_from_file(path, "1.1.0".parse().unwrap(), |_| {}));
+    (result, std::fs::read(&appimage).unwrap())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn install_from_file_verifies_signature() {
+    let key = SigningKey::new();
+    let dir = tempfile::tempdir().unwrap();
+    let path = package_file(dir.path(), Some(&key.sign(&package())));
+
+    let (result, installed) = install_from_file(dir.path(), &key, &path);
+    result.unwrap();
+    assert_eq!(installed, package());
+}
+
+#[test]
+fn install_from_file_rejects_missing_signature() {
+    let key = SigningKey::new();
+    let dir = tempfile::tempdir().unwrap();
+    let path = package_file(dir.path(), None);
+
+    let (result, installed) = install_from_file(dir.path(), &key, &path);
+    assert!(matches!(result, Err(Error::MissingSignature)), "{result:?}");
+    assert_eq!(installed, b"\x7fELF old version");
+}
+
+#[test]
+fn install_from_file_rejects_invalid_signature() {
+    let key = SigningKey::new();
+    let dir = tempfile::tempdir().unwrap();
+    // signed by another key
+    let path = package_file(dir.path(), Some(&SigningKey::new().sign(&package())));
+
+    let (result, installed) = install_from_file(dir.path(), &key, &path);
+    let Err(error) = result else {
+        panic!("the install succeeded");
+    };
+    assert_eq!(error.code(), "signature");
+    assert_eq!(installed, b"\x7fELF old version");
+}
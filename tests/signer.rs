@@ -0,0 +1,71 @@
+#![cfg(feature = "cli")]
+
+use std::{
+    fs,
+    path::Path,
+    process::{Command, Output},
+};
+
+use tauri_plugin_universal_updater::verify_signature;
+
+fn signer(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_universal-updater-signer"))
+        .current_dir(dir)
+        .args(args)
+        .env_remove("UNIVERSAL_UPDATER_PRIVATE_KEY")
+        .env_remove("UNIVERSAL_UPDATER_PUBLIC_KEY")
+        .env_remove("UNIVERSAL_UPDATER_PRIVATE_KEY_PASSWORD")
+        .output()
+        .expect("failed to run the signer")
+}
+
+#[test]
+fn generated_key_signs_and_verifies() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = signer(dir.path(), &["generate", "--output", "app.key"]);
+    assert!(output.status.success(), "{output:?}");
+    let pubkey = fs::read_to_string(dir.path().join("app.key.pub")).unwrap();
+    assert_eq!(String::from_utf8(output.stdout).unwrap().trim(), pubkey);
+
+    fs::write(dir.path().join("app.tar.gz"), b"package").unwrap();
+    let output = signer(dir.path(), &["sign", "-f", "app.key", "app.tar.gz"]);
+    assert!(output.status.success(), "{output:?}");
+    let signature = fs::read_to_string(dir.path().join("app.tar.gz.sig")).unwrap();
+    verify_signature(b"package", &signature, &pubkey).unwrap();
+
+    let output = signer(dir.path(), &["verify", "-f", "app.key.pub", "app.tar.gz"]);
+    assert!(output.status.success(), "{output:?}");
+
+    fs::write(dir.path().join("app.tar.gz"), b"tampered").unwrap();
+    let output = signer(dir.path(), &["verify", "-k", &pubkey, "app.tar.gz"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("signature verification failed"));
+}
+
+#[test]
+fn generate_keeps_existing_keys_without_force() {
+    let dir = tempfile::tempdir().unwrap();
+    assert!(signer(dir.path(), &["generate", "--output", "app.key"])
+        .status
+        .success());
+    let pubkey = fs::read_to_string(dir.path().join("app.key.pub")).unwrap();
+
+    let output = signer(dir.path(), &["generate", "--output", "app.key"]);
+    assert!(!output.status.success());
+    assert_eq!(
+        fs::read_to_string(dir.path().join("app.key.pub")).unwrap(),
+        pubkey
+    );
+
+    assert!(
+        signer(dir.path(), &["generate", "--output", "app.key", "--force"])
+            .status
+            .success()
+    );
+    assert_ne!(
+        fs::read_to_string(dir.path().join("app.key.pub")).unwrap(),
+        pubkey
+    );
+}
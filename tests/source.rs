@@ -0,0 +1,99 @@
+mod common;
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use common::{app_with, json_target, run, updater};
+use semver::Version;
+use serde_json::json;
+use tauri_plugin_universal_updater::{
+    Builder, Error, ReleaseManifestPlatform, RemoteRelease, Result, UpdateSource,
+};
+
+/// A source offering a fixed version, recording the arguments it is called with.
+#[derive(Clone, Default)]
+struct StaticSource {
+    version: Option<&'static str>,
+    calls: Arc<Mutex<Vec<(String, String)>>>,
+}
+
+#[async_trait]
+impl UpdateSource for StaticSource {
+    async fn latest_release(
+        &self,
+        target: &str,
+        current_version: &Version,
+    ) -> Result<Option<RemoteRelease>> {
+        self.calls
+            .lock()
+            .unwrap()
+            .push((target.to_string(), current_version.to_string()));
+        let Some(version) = self.version else {
+            return Err(Error::Network("offline".into()));
+        };
+
+        let platform = ReleaseManifestPlatform {
+            url: "https://example.com/app.tar.gz".parse().unwrap(),
+            urls: Vec::new(),
+            sha256: None,
+            signature: None,
+            patches: Vec::new(),
+        };
+        Ok(Some(RemoteRelease {
+            version: version.parse().unwrap(),
+            notes: Some("from a custom source".into()),
+            pub_date: None,
+            expires: None,
+            platforms: HashMap::from([(target.to_string(), platform)]),
+        }))
+    }
+}
+
+#[test]
+fn check_uses_builder_source() {
+    let source = StaticSource {
+        version: Some("1.1.0"),
+        ..Default::default()
+    };
+    // the source takes precedence over the configured endpoint
+    let app = app_with(
+        Builder::new().source(source.clone()),
+        json!({ "endpoint": "https://example.com/latest.json" }),
+    );
+
+    let update = run(updater(&app).check()).unwrap().unwrap();
+    assert_eq!(update.version(), "1.1.0");
+    assert_eq!(update.body(), Some("from a custom source"));
+    assert_eq!(
+        update.download_url().as_str(),
+        "https://example.com/app.tar.gz"
+    );
+    assert_eq!(
+        *source.calls.lock().unwrap(),
+        [(json_target(), "1.0.0".to_string())]
+    );
+}
+
+#[test]
+fn check_without_endpoint_uses_builder_source() {
+    let source = StaticSource {
+        version: Some("1.0.0"),
+        ..Default::default()
+    };
+    let app = app_with(Builder::new().source(source), json!({}));
+
+    assert!(run(updater(&app).check()).unwrap().is_none());
+}
+
+#[test]
+fn check_returns_source_errors() {
+    let app = app_with(Builder::new().source(StaticSource::default()), json!({}));
+
+    let Err(error) = run(updater(&app).check()) else {
+        panic!("the check succeeded");
+    };
+    assert!(matches!(error, Error::Network(_)), "{error:?}");
+}
@@ -0,0 +1,101 @@
+mod common;
+
+use std::{
+    fmt::Debug,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use common::{app, json_target, run, updater, MockResponse, MockServer};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+    Event, Metadata, Subscriber,
+};
+
+/// A subscriber recording the spans created with their fields, e.g. `download version=1.1.0`.
+#[derive(Clone, Default)]
+struct Recorder {
+    spans: Arc<Mutex<Vec<String>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+struct Fields<'a>(&'a mut String);
+
+impl Visit for Fields<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0.push_str(&format!(" {}={value:?}", field.name()));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.push_str(&format!(" {}={value}", field.name()));
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let mut description = span.metadata().name().to_string();
+        span.record(&mut Fields(&mut description));
+        self.spans.lock().unwrap().push(description);
+        Id::from_u64(self.next_id.fetch_add(1, Ordering::SeqCst) + 1)
+    }
+
+    fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, _event: &Event<'_>) {}
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+}
+
+#[test]
+fn check_and_download_are_traced() {
+    let server = MockServer::start();
+    let package = b"package".to_vec();
+    server.mock("/app.tar.gz", MockResponse::bytes(package.clone()));
+    server.mock(
+        "/latest.json",
+        MockResponse::json(&json!({
+            "version": "1.1.0",
+            "platforms": {
+                json_target(): {
+                    "url": server.url("/app.tar.gz"),
+                    "sha256": hex::encode(Sha256::digest(&package))
+                }
+            }
+        })),
+    );
+    let app = app(json!({ "endpoint": server.url("/latest.json") }));
+
+    let recorder = Recorder::default();
+    tracing::subscriber::with_default(recorder.clone(), || {
+        let update = run(updater(&app).check()).unwrap().unwrap();
+        run(update.download(app.handle(), |_| {})).unwrap();
+    });
+
+    let spans = recorder.spans.lock().unwrap();
+    assert!(
+        spans.contains(&format!(
+            "check target={} current_version=1.0.0",
+            json_target()
+        )),
+        "{spans:?}"
+    );
+    assert!(
+        spans
+            .iter()
+            .any(|span| span.starts_with("download version=1.1.0")),
+        "{spans:?}"
+    );
+}